@@ -0,0 +1,479 @@
+//! Dot products and weighted averages over slices of fixed-point values
+//!
+//! Products are accumulated in a signed 256-bit sum and the result is rounded once at
+//! the end, so the result is exact up to the final rounding. The running sum can still
+//! exceed 256 bits before later terms offset it, which returns `PhantomOverflow` even
+//! if the result would fit. This requires several products of values near the bounds
+//! of `i128` or `u128` with the same sign in a row.
+
+use crate::{
+    error::FixedPointError,
+    integer::{from_magnitude, magnitude, Integer},
+    rounding::{Remainder, Rounding},
+    u256::U256,
+};
+
+/// Safely calculates sum(x[i] * y[i]) / scale, such as the value of a portfolio from its
+/// balances and prices, rounded in the direction of `rounding`.
+///
+/// Returns an error if the slices have different lengths, if the scale is not
+/// positive, if the running sum overflows 256 bits, or if the result does not fit in `T`.
+pub fn try_fixed_dot<T: Integer>(
+    x: &[T],
+    y: &[T],
+    scale: T,
+    rounding: Rounding,
+) -> Result<T, FixedPointError> {
+    if x.len() != y.len() {
+        return Err(FixedPointError::InvalidInput);
+    }
+    dot(x.iter().copied().zip(y.iter().copied()), scale, rounding)
+}
+
+/// Safely calculates sum(x[i] * y[i]) / scale, such as the value of a portfolio from its
+/// balances and prices, rounded in the direction of `rounding`.
+///
+/// Returns None if the slices have different lengths, if the scale is not positive, or
+/// if an overflow occurs.
+pub fn fixed_dot<T: Integer>(x: &[T], y: &[T], scale: T, rounding: Rounding) -> Option<T> {
+    try_fixed_dot(x, y, scale, rounding).ok()
+}
+
+/// Safely calculates sum(values[i] * weights[i]) / sum(weights), rounded in the direction
+/// of `rounding`. The result has the scale of `values`, and the weights can have any
+/// scale.
+///
+/// Returns an error if the slices have different lengths, if any weight is negative, if
+/// the weights sum to 0, or if an intermediate sum overflows.
+pub fn try_weighted_average<T: Integer>(
+    values: &[T],
+    weights: &[T],
+    rounding: Rounding,
+) -> Result<T, FixedPointError> {
+    if values.len() != weights.len() {
+        return Err(FixedPointError::InvalidInput);
+    }
+    weighted_average_of(
+        values.iter().copied().zip(weights.iter().copied()),
+        rounding,
+    )
+}
+
+/// Safely calculates sum(values[i] * weights[i]) / sum(weights), rounded in the direction
+/// of `rounding`. The result has the scale of `values`, and the weights can have any
+/// scale.
+///
+/// Returns None if the slices have different lengths, if any weight is negative, if the
+/// weights sum to 0, or if an overflow occurs.
+pub fn weighted_average<T: Integer>(values: &[T], weights: &[T], rounding: Rounding) -> Option<T> {
+    try_weighted_average(values, weights, rounding).ok()
+}
+
+/// Safely calculates sum(x[i] * y[i]) / scale over two `soroban_sdk::Vec`s, rounded in
+/// the direction of `rounding`. See `try_fixed_dot` for details.
+///
+/// Only available with the `soroban` feature.
+#[cfg(feature = "soroban")]
+pub fn try_fixed_dot_vec(
+    x: &soroban_sdk::Vec<i128>,
+    y: &soroban_sdk::Vec<i128>,
+    scale: i128,
+    rounding: Rounding,
+) -> Result<i128, FixedPointError> {
+    if x.len() != y.len() {
+        return Err(FixedPointError::InvalidInput);
+    }
+    dot(x.iter().zip(y.iter()), scale, rounding)
+}
+
+/// Safely calculates sum(x[i] * y[i]) / scale over two `soroban_sdk::Vec`s, rounded in
+/// the direction of `rounding`. See `try_fixed_dot` for details.
+///
+/// Only available with the `soroban` feature.
+#[cfg(feature = "soroban")]
+pub fn fixed_dot_vec(
+    x: &soroban_sdk::Vec<i128>,
+    y: &soroban_sdk::Vec<i128>,
+    scale: i128,
+    rounding: Rounding,
+) -> Option<i128> {
+    try_fixed_dot_vec(x, y, scale, rounding).ok()
+}
+
+/// Safely calculates sum(values[i] * weights[i]) / sum(weights) over two
+/// `soroban_sdk::Vec`s, rounded in the direction of `rounding`. See
+/// `try_weighted_average` for details.
+///
+/// Only available with the `soroban` feature.
+#[cfg(feature = "soroban")]
+pub fn try_weighted_average_vec(
+    values: &soroban_sdk::Vec<i128>,
+    weights: &soroban_sdk::Vec<i128>,
+    rounding: Rounding,
+) -> Result<i128, FixedPointError> {
+    if values.len() != weights.len() {
+        return Err(FixedPointError::InvalidInput);
+    }
+    weighted_average_of(values.iter().zip(weights.iter()), rounding)
+}
+
+/// Safely calculates sum(values[i] * weights[i]) / sum(weights) over two
+/// `soroban_sdk::Vec`s, rounded in the direction of `rounding`. See
+/// `try_weighted_average` for details.
+///
+/// Only available with the `soroban` feature.
+#[cfg(feature = "soroban")]
+pub fn weighted_average_vec(
+    values: &soroban_sdk::Vec<i128>,
+    weights: &soroban_sdk::Vec<i128>,
+    rounding: Rounding,
+) -> Option<i128> {
+    try_weighted_average_vec(values, weights, rounding).ok()
+}
+
+fn dot<T: Integer>(
+    pairs: impl Iterator<Item = (T, T)>,
+    scale: T,
+    rounding: Rounding,
+) -> Result<T, FixedPointError> {
+    if scale <= T::ZERO {
+        return Err(if scale == T::ZERO {
+            FixedPointError::DivideByZero
+        } else {
+            FixedPointError::InvalidInput
+        });
+    }
+    let mut sum = WideSum::default();
+    for (x, y) in pairs {
+        sum.add_product(x, y)?;
+    }
+    let scale = magnitude(scale).ok_or(FixedPointError::Overflow)?;
+    sum.div(U256::from_u128(scale), rounding)
+}
+
+fn weighted_average_of<T: Integer>(
+    pairs: impl Iterator<Item = (T, T)>,
+    rounding: Rounding,
+) -> Result<T, FixedPointError> {
+    let mut sum = WideSum::default();
+    let mut total_weight = U256::default();
+    for (value, weight) in pairs {
+        if weight < T::ZERO {
+            return Err(FixedPointError::InvalidInput);
+        }
+        sum.add_product(value, weight)?;
+        total_weight = magnitude(weight)
+            .and_then(|weight| total_weight.checked_add_u128(weight))
+            .ok_or(FixedPointError::PhantomOverflow)?;
+    }
+    if total_weight.is_zero() {
+        return Err(FixedPointError::DivideByZero);
+    }
+    sum.div(total_weight, rounding)
+}
+
+/// A signed 256-bit sum, kept as its sign and magnitude
+#[derive(Default)]
+struct WideSum {
+    negative: bool,
+    magnitude: U256,
+}
+
+impl WideSum {
+    fn add_product<T: Integer>(&mut self, x: T, y: T) -> Result<(), FixedPointError> {
+        let product = U256::mul_u128(
+            magnitude(x).ok_or(FixedPointError::Overflow)?,
+            magnitude(y).ok_or(FixedPointError::Overflow)?,
+        );
+        let negative = (x < T::ZERO) != (y < T::ZERO);
+        if negative == self.negative || self.magnitude.is_zero() {
+            self.magnitude = self
+                .magnitude
+                .checked_add(product)
+                .ok_or(FixedPointError::PhantomOverflow)?;
+            self.negative = negative;
+        } else if self.magnitude >= product {
+            // the product offsets part of the sum, so the sign is unchanged
+            self.magnitude = self
+                .magnitude
+                .checked_sub(product)
+                .ok_or(FixedPointError::PhantomOverflow)?;
+        } else {
+            self.magnitude = product
+                .checked_sub(self.magnitude)
+                .ok_or(FixedPointError::PhantomOverflow)?;
+            self.negative = negative;
+        }
+        Ok(())
+    }
+
+    /// Divides the sum by a positive divisor, rounded in the direction of `rounding`
+    fn div<T: Integer>(self, divisor: U256, rounding: Rounding) -> Result<T, FixedPointError> {
+        let negative = self.negative && !self.magnitude.is_zero();
+        let error = if negative {
+            FixedPointError::Underflow
+        } else {
+            FixedPointError::Overflow
+        };
+        let (quotient, remainder) = self
+            .magnitude
+            .div_rem(divisor)
+            .ok_or(FixedPointError::DivideByZero)?;
+        let mut quotient = quotient.to_u128().ok_or(error)?;
+        if rounding.round_away_from_zero(
+            negative,
+            quotient % 2 == 1,
+            Remainder::from_u256(remainder, divisor),
+        ) {
+            quotient = quotient.checked_add(1).ok_or(error)?;
+        }
+        from_magnitude(negative, quotient).ok_or(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_dot() {
+        let balances: [i128; 3] = [100_0000000, 2_5000000, 3333_3333333];
+        let prices: [i128; 3] = [1_0000000, 20_1234567, 1234567];
+
+        // 100 + 50.30864175 + 411.52233332... = 561.83097507...
+        assert_eq!(
+            fixed_dot(&balances, &prices, 1_0000000, Rounding::Floor),
+            Some(561_8309750)
+        );
+        assert_eq!(
+            fixed_dot(&balances, &prices, 1_0000000, Rounding::Ceil),
+            Some(561_8309751)
+        );
+        assert_eq!(
+            fixed_dot::<u64>(&[], &[], 1_0000000, Rounding::Floor),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_fixed_dot_rounds_once() {
+        let x: [u64; 3] = [1, 1, 1];
+        let y: [u64; 3] = [4, 4, 4];
+
+        // rounding each product down would return 0
+        assert_eq!(fixed_dot(&x, &y, 10, Rounding::Floor), Some(1));
+        assert_eq!(fixed_dot(&x, &y, 10, Rounding::HalfUp), Some(1));
+        assert_eq!(fixed_dot(&x, &y, 10, Rounding::Ceil), Some(2));
+    }
+
+    #[test]
+    fn test_fixed_dot_negative_terms() {
+        let x: [i128; 3] = [3_0000000, -5_0000000, 1_5000000];
+        let y: [i128; 3] = [1_2500000, 1_0000001, -1];
+
+        // 3.75 - 5.0000005 - 0.00000015 = -1.25000065
+        assert_eq!(
+            fixed_dot(&x, &y, 1_0000000, Rounding::Floor),
+            Some(-1_2500007)
+        );
+        assert_eq!(
+            fixed_dot(&x, &y, 1_0000000, Rounding::TowardZero),
+            Some(-1_2500006)
+        );
+    }
+
+    #[test]
+    fn test_fixed_dot_intermediate_overflow() {
+        let x: [i128; 3] = [i128::MAX, i128::MAX, -i128::MAX];
+        let y: [i128; 3] = [i128::MAX, i128::MAX, i128::MAX];
+
+        // the running sum exceeds i128, but the result fits
+        assert_eq!(
+            fixed_dot(&x, &y, i128::MAX, Rounding::Floor),
+            Some(i128::MAX)
+        );
+        assert_eq!(
+            try_fixed_dot(&x[..2], &y[..2], i128::MAX, Rounding::Floor),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!(
+            try_fixed_dot(
+                &[-i128::MAX, -i128::MAX],
+                &y[..2],
+                i128::MAX,
+                Rounding::Floor
+            ),
+            Err(FixedPointError::Underflow)
+        );
+    }
+
+    #[test]
+    fn test_fixed_dot_offsetting_terms() {
+        let x = [i128::MIN; 8];
+        let (min, max) = (i128::MIN, i128::MAX);
+
+        // each pair of products sums to 2^254 - (2^254 - 2^127) = 2^127
+        assert_eq!(
+            fixed_dot(
+                &x,
+                &[min, max, min, max, min, max, min, max],
+                max,
+                Rounding::Floor
+            ),
+            Some(4)
+        );
+        // the first four products sum to 2^256 before the rest offset them
+        assert_eq!(
+            try_fixed_dot(
+                &x,
+                &[min, min, min, min, max, max, max, max],
+                max,
+                Rounding::Floor
+            ),
+            Err(FixedPointError::PhantomOverflow)
+        );
+    }
+
+    #[test]
+    fn test_fixed_dot_errors() {
+        assert_eq!(
+            try_fixed_dot(&[1_i128, 2], &[1], 1, Rounding::Floor),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_fixed_dot(&[1_i128], &[1], 0, Rounding::Floor),
+            Err(FixedPointError::DivideByZero)
+        );
+        assert_eq!(
+            try_fixed_dot(&[1_i128], &[1], -1, Rounding::Floor),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_fixed_dot(&[u128::MAX; 5], &[u128::MAX; 5], 1, Rounding::Floor),
+            Err(FixedPointError::PhantomOverflow)
+        );
+    }
+
+    #[test]
+    fn test_weighted_average() {
+        let prices: [i128; 3] = [10_0000000, 11_0000000, 12_5000000];
+        let volumes: [i128; 3] = [3_0000000, 1_0000000, 2_0000000];
+
+        // (30 + 11 + 25) / 6 = 11
+        assert_eq!(
+            weighted_average(&prices, &volumes, Rounding::Floor),
+            Some(11_0000000)
+        );
+        // (10 + 11 + 12.5) / 3 = 11.1666666...
+        assert_eq!(
+            weighted_average(&prices, &[1, 1, 1], Rounding::Floor),
+            Some(11_1666666)
+        );
+        assert_eq!(
+            weighted_average(&prices, &[1, 1, 1], Rounding::Ceil),
+            Some(11_1666667)
+        );
+        assert_eq!(
+            weighted_average(&[-1_0000000_i64, 2_0000000], &[2, 1], Rounding::Floor),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_weighted_average_large_values() {
+        let values = [u128::MAX, u128::MAX - 2];
+        let weights = [u128::MAX / 2, u128::MAX / 2];
+
+        assert_eq!(
+            weighted_average(&values, &weights, Rounding::Floor),
+            Some(u128::MAX - 1)
+        );
+    }
+
+    #[test]
+    fn test_weighted_average_large_total_weight() {
+        // the weights sum to more than u128::MAX
+        let weights = [u128::MAX, u128::MAX];
+
+        assert_eq!(
+            weighted_average(&[3, 4], &weights, Rounding::Floor),
+            Some(3)
+        );
+        assert_eq!(
+            weighted_average(&[3, 4], &weights, Rounding::HalfUp),
+            Some(4)
+        );
+        // (u128::MAX + 2) / (u128::MAX + 1)
+        assert_eq!(
+            weighted_average(&[1, 2], &[u128::MAX, 1], Rounding::Ceil),
+            Some(2)
+        );
+        assert_eq!(
+            weighted_average(
+                &[1_0000000_i128, 2_0000000, 3_0000000],
+                &[i128::MAX; 3],
+                Rounding::Floor
+            ),
+            Some(2_0000000)
+        );
+    }
+
+    #[test]
+    fn test_weighted_average_errors() {
+        assert_eq!(
+            try_weighted_average(&[1_i128], &[1, 2], Rounding::Floor),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_weighted_average(&[1_i128, 2], &[1, -1], Rounding::Floor),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_weighted_average(&[1_i128, 2], &[0, 0], Rounding::Floor),
+            Err(FixedPointError::DivideByZero)
+        );
+        assert_eq!(
+            try_weighted_average::<u64>(&[], &[], Rounding::Floor),
+            Err(FixedPointError::DivideByZero)
+        );
+    }
+}
+
+#[cfg(all(test, feature = "testutils"))]
+mod soroban_tests {
+    use soroban_sdk::{Env, Vec};
+
+    use super::*;
+
+    #[test]
+    fn test_fixed_dot_vec() {
+        let e = Env::default();
+        let balances = Vec::from_array(&e, [100_0000000_i128, 2_5000000, 3333_3333333]);
+        let prices = Vec::from_array(&e, [1_0000000_i128, 20_1234567, 1234567]);
+
+        assert_eq!(
+            fixed_dot_vec(&balances, &prices, 1_0000000, Rounding::Floor),
+            Some(561_8309750)
+        );
+        assert_eq!(
+            try_fixed_dot_vec(&balances, &Vec::new(&e), 1_0000000, Rounding::Floor),
+            Err(FixedPointError::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn test_weighted_average_vec() {
+        let e = Env::default();
+        let prices = Vec::from_array(&e, [10_0000000_i128, 11_0000000, 12_5000000]);
+        let volumes = Vec::from_array(&e, [3_0000000_i128, 1_0000000, 2_0000000]);
+
+        assert_eq!(
+            weighted_average_vec(&prices, &volumes, Rounding::Floor),
+            Some(11_0000000)
+        );
+        assert_eq!(
+            try_weighted_average_vec(&prices, &Vec::new(&e), Rounding::Floor),
+            Err(FixedPointError::InvalidInput)
+        );
+    }
+}
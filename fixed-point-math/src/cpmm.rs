@@ -0,0 +1,333 @@
+//! Swap and liquidity math for constant-product (x * y = k) pools
+//!
+//! Every result is rounded in favor of the pool. Fees are expressed in basis points,
+//! and intermediate products are computed without phantom overflows.
+
+#[allow(deprecated)]
+use crate::{
+    error::FixedPointError,
+    fixed_point::{FixedPoint, BPS_DENOMINATOR},
+    integer::{magnitude, Integer},
+    rounding::Rounding,
+    u256::U256,
+};
+
+/// Safely calculates the amount received for swapping `amount_in` into the pool,
+/// after a fee of `fee_bps` is taken from `amount_in`. The result is rounded down.
+///
+/// Returns an error if any value is negative, if the fee is not between 0 and 9,999
+/// bps, or if the pool is empty.
+#[allow(deprecated)]
+pub fn try_get_amount_out<T: FixedPoint + Integer>(
+    amount_in: T,
+    reserve_in: T,
+    reserve_out: T,
+    fee_bps: T,
+) -> Result<T, FixedPointError> {
+    check_non_negative(&[amount_in, reserve_in, reserve_out])?;
+    let denominator = check_fee(fee_bps)?;
+    let amount_in = amount_in.try_apply_bps_rounded(denominator - fee_bps, Rounding::Floor)?;
+    // amount_out = reserve_out * amount_in / (reserve_in + amount_in)
+    let new_reserve_in = reserve_in
+        .checked_add(amount_in)
+        .ok_or(FixedPointError::Overflow)?;
+    amount_in.try_fixed_mul_floor(reserve_out, new_reserve_in)
+}
+
+/// Safely calculates the amount received for swapping `amount_in` into the pool,
+/// after a fee of `fee_bps` is taken from `amount_in`. The result is rounded down.
+///
+/// Returns None if any value is negative, if the fee is not between 0 and 9,999 bps,
+/// or if the pool is empty.
+#[allow(deprecated)]
+pub fn get_amount_out<T: FixedPoint + Integer>(
+    amount_in: T,
+    reserve_in: T,
+    reserve_out: T,
+    fee_bps: T,
+) -> Option<T> {
+    try_get_amount_out(amount_in, reserve_in, reserve_out, fee_bps).ok()
+}
+
+/// Safely calculates the amount that must be swapped into the pool to receive
+/// `amount_out`, including a fee of `fee_bps`. The result is rounded up.
+///
+/// Returns an error if any value is negative, if the fee is not between 0 and 9,999
+/// bps, or if `amount_out` is not less than `reserve_out`.
+#[allow(deprecated)]
+pub fn try_get_amount_in<T: FixedPoint + Integer>(
+    amount_out: T,
+    reserve_in: T,
+    reserve_out: T,
+    fee_bps: T,
+) -> Result<T, FixedPointError> {
+    check_non_negative(&[amount_out, reserve_in, reserve_out])?;
+    check_fee(fee_bps)?;
+    if amount_out >= reserve_out {
+        return Err(FixedPointError::InvalidInput);
+    }
+    // amount_in = reserve_in * amount_out / (reserve_out - amount_out)
+    let amount_in = amount_out.try_fixed_mul_ceil(reserve_in, reserve_out - amount_out)?;
+    amount_in.try_gross_up_for_fee(fee_bps)
+}
+
+/// Safely calculates the amount that must be swapped into the pool to receive
+/// `amount_out`, including a fee of `fee_bps`. The result is rounded up.
+///
+/// Returns None if any value is negative, if the fee is not between 0 and 9,999 bps,
+/// or if `amount_out` is not less than `reserve_out`.
+#[allow(deprecated)]
+pub fn get_amount_in<T: FixedPoint + Integer>(
+    amount_out: T,
+    reserve_in: T,
+    reserve_out: T,
+    fee_bps: T,
+) -> Option<T> {
+    try_get_amount_in(amount_out, reserve_in, reserve_out, fee_bps).ok()
+}
+
+/// Safely calculates the shares minted for depositing `amount_a` and `amount_b`,
+/// rounded down.
+///
+/// The first deposit into an empty pool mints sqrt(amount_a * amount_b) shares.
+/// Afterwards, shares are minted in proportion to the smaller share of either
+/// reserve, so any excess of one token is donated to the pool.
+///
+/// Returns an error if any value is negative, or if the pool has shares but an
+/// empty reserve.
+#[allow(deprecated)]
+pub fn try_liquidity_minted<T: FixedPoint + Integer>(
+    amount_a: T,
+    amount_b: T,
+    reserve_a: T,
+    reserve_b: T,
+    total_shares: T,
+) -> Result<T, FixedPointError> {
+    check_non_negative(&[amount_a, amount_b, reserve_a, reserve_b, total_shares])?;
+    if total_shares == T::ZERO {
+        return geometric_mean(amount_a, amount_b);
+    }
+    let shares_a = amount_a.try_fixed_mul_floor(total_shares, reserve_a)?;
+    let shares_b = amount_b.try_fixed_mul_floor(total_shares, reserve_b)?;
+    Ok(shares_a.min(shares_b))
+}
+
+/// Safely calculates the shares minted for depositing `amount_a` and `amount_b`,
+/// rounded down. See `try_liquidity_minted` for details.
+///
+/// Returns None if any value is negative, or if the pool has shares but an empty
+/// reserve.
+#[allow(deprecated)]
+pub fn liquidity_minted<T: FixedPoint + Integer>(
+    amount_a: T,
+    amount_b: T,
+    reserve_a: T,
+    reserve_b: T,
+    total_shares: T,
+) -> Option<T> {
+    try_liquidity_minted(amount_a, amount_b, reserve_a, reserve_b, total_shares).ok()
+}
+
+/// Safely calculates the amounts of each token withdrawn for burning `shares`,
+/// rounded down.
+///
+/// Returns an error if any value is negative, if `shares` exceeds `total_shares`,
+/// or if the pool has no shares.
+#[allow(deprecated)]
+pub fn try_liquidity_burned<T: FixedPoint + Integer>(
+    shares: T,
+    reserve_a: T,
+    reserve_b: T,
+    total_shares: T,
+) -> Result<(T, T), FixedPointError> {
+    check_non_negative(&[shares, reserve_a, reserve_b, total_shares])?;
+    if shares > total_shares {
+        return Err(FixedPointError::InvalidInput);
+    }
+    let amount_a = shares.try_fixed_mul_floor(reserve_a, total_shares)?;
+    let amount_b = shares.try_fixed_mul_floor(reserve_b, total_shares)?;
+    Ok((amount_a, amount_b))
+}
+
+/// Safely calculates the amounts of each token withdrawn for burning `shares`,
+/// rounded down.
+///
+/// Returns None if any value is negative, if `shares` exceeds `total_shares`, or if
+/// the pool has no shares.
+#[allow(deprecated)]
+pub fn liquidity_burned<T: FixedPoint + Integer>(
+    shares: T,
+    reserve_a: T,
+    reserve_b: T,
+    total_shares: T,
+) -> Option<(T, T)> {
+    try_liquidity_burned(shares, reserve_a, reserve_b, total_shares).ok()
+}
+
+/// Calculates floor(sqrt(x * y)) for non-negative x and y. The product is held in 256
+/// bits, and the root is at most the larger of x and y, so it always fits in `T`.
+fn geometric_mean<T: Integer>(x: T, y: T) -> Result<T, FixedPointError> {
+    let x = magnitude(x).ok_or(FixedPointError::InvalidInput)?;
+    let y = magnitude(y).ok_or(FixedPointError::InvalidInput)?;
+    T::from_u128(U256::mul_u128(x, y).sqrt()).ok_or(FixedPointError::Overflow)
+}
+
+fn check_non_negative<T: Integer>(values: &[T]) -> Result<(), FixedPointError> {
+    if values.iter().any(|value| *value < T::ZERO) {
+        return Err(FixedPointError::InvalidInput);
+    }
+    Ok(())
+}
+
+/// Validates the fee, and returns the basis point denominator
+fn check_fee<T: Integer>(fee_bps: T) -> Result<T, FixedPointError> {
+    let denominator = T::from_i128(BPS_DENOMINATOR).ok_or(FixedPointError::Overflow)?;
+    if fee_bps < T::ZERO || fee_bps >= denominator {
+        return Err(FixedPointError::InvalidInput);
+    }
+    Ok(denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_amount_out() {
+        let reserve: i128 = 1000_0000000;
+
+        let result = get_amount_out(10_0000000, reserve, reserve, 30).unwrap();
+
+        // 9.97 * 1000 / 1009.97 = 9.87158034...
+        assert_eq!(result, 9_8715803);
+        assert_eq!(
+            get_amount_out(10_0000000_u64, 1000_0000000, 1000_0000000, 0),
+            Some(9_9009900)
+        );
+        assert_eq!(get_amount_out(0_i128, reserve, reserve, 30), Some(0));
+    }
+
+    #[test]
+    fn test_get_amount_in() {
+        let reserve: i128 = 1000_0000000;
+
+        let result = get_amount_in(5_0000000, reserve, reserve, 30).unwrap();
+
+        assert_eq!(result, 5_0402465);
+        // swapping the amount in returns at least the requested amount out
+        assert_eq!(
+            get_amount_out(result, reserve, reserve, 30),
+            Some(5_0000000)
+        );
+    }
+
+    #[test]
+    fn test_swap_round_trip_favors_pool() {
+        let reserve_in: u64 = 12345_6789012;
+        let reserve_out: u64 = 987_6543210;
+
+        for amount_out in [1, 7, 1_0000000, 123_4567890] {
+            let amount_in = get_amount_in(amount_out, reserve_in, reserve_out, 25).unwrap();
+            let received = get_amount_out(amount_in, reserve_in, reserve_out, 25).unwrap();
+            assert!(received >= amount_out);
+            let received = get_amount_out(amount_in - 1, reserve_in, reserve_out, 25).unwrap();
+            assert!(received <= amount_out);
+        }
+    }
+
+    #[test]
+    fn test_get_amount_out_phantom_overflow() {
+        let reserve: i128 = 1_000_000_000_000_000_000_000_000_000_000;
+
+        let result = get_amount_out(reserve / 10, reserve, reserve, 30).unwrap();
+
+        assert_eq!(result, 90_661_089_388_014_913_158_134_036_555);
+    }
+
+    #[test]
+    fn test_swap_errors() {
+        assert_eq!(
+            try_get_amount_out(1_i128, 100, 100, 10_000),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_get_amount_out(-1_i128, 100, 100, 30),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_get_amount_out(0_i128, 0, 100, 30),
+            Err(FixedPointError::DivideByZero)
+        );
+        assert_eq!(
+            try_get_amount_in(100_i128, 100, 100, 30),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_get_amount_in(99_u64, u64::MAX, 100, 30),
+            Err(FixedPointError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_liquidity_minted() {
+        // the first deposit mints sqrt(25 * 100) = 50
+        assert_eq!(
+            liquidity_minted(25_0000000_i128, 100_0000000, 0, 0, 0),
+            Some(50_0000000)
+        );
+        assert_eq!(liquidity_minted(25_0000000_u64, 0, 0, 0, 0), Some(0));
+        // later deposits mint the smaller proportion
+        assert_eq!(
+            liquidity_minted(
+                10_0000000_i128,
+                20_0000000,
+                100_0000000,
+                150_0000000,
+                777_0000000
+            ),
+            Some(77_7000000)
+        );
+    }
+
+    #[test]
+    fn test_liquidity_minted_phantom_overflow() {
+        let amount: i128 = i128::MAX / 2;
+
+        assert_eq!(liquidity_minted(amount, amount, 0, 0, 0), Some(amount));
+        assert_eq!(
+            liquidity_minted(amount, amount, amount, amount, amount),
+            Some(amount)
+        );
+    }
+
+    #[test]
+    fn test_liquidity_burned() {
+        let result = liquidity_burned(123_4567890_i128, 1000_0000000, 2500_0000000, 777_0000000);
+
+        assert_eq!(result, Some((158_8890463, 397_2226158)));
+        assert_eq!(
+            liquidity_burned(777_0000000_u64, 1000_0000000, 2500_0000000, 777_0000000),
+            Some((1000_0000000, 2500_0000000))
+        );
+    }
+
+    #[test]
+    fn test_liquidity_errors() {
+        assert_eq!(
+            try_liquidity_minted(1_i128, 1, 0, 100, 100),
+            Err(FixedPointError::DivideByZero)
+        );
+        assert_eq!(
+            try_liquidity_minted(-1_i128, 1, 0, 0, 0),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_liquidity_burned(101_i128, 100, 100, 100),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_liquidity_burned(0_u64, 100, 100, 0),
+            Err(FixedPointError::DivideByZero)
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//! Linear interpolation and piecewise-linear curves, such as vesting schedules,
+//! dynamic fees or auction price decay
+//!
+//! Values are interpolated with `try_fixed_mul`, picking the rounding of the delta so
+//! that y itself is rounded once in the direction of a `Rounding`. Every failure is
+//! returned as an error instead of panicking.
+
+#[allow(deprecated)]
+use crate::{
+    error::FixedPointError, fixed_point::FixedPoint, integer::Integer, rounding::Rounding,
+};
+
+/// Safely calculates y at `x` on the line through (x0, y0) and (x1, y1), rounded in the
+/// direction of `rounding`. `x` is clamped to the range [x0, x1], so the result is
+/// always between y0 and y1.
+///
+/// The rounding is applied to y itself, so `TowardZero`, `AwayFromZero` and the
+/// nearest modes depend on the sign of y, for both increasing and decreasing lines.
+///
+/// Returns an error if x0 is not less than x1, or if the difference between the
+/// endpoints does not fit in `T`.
+#[allow(deprecated)]
+pub fn try_lerp<T: FixedPoint + Integer>(
+    x0: T,
+    y0: T,
+    x1: T,
+    y1: T,
+    x: T,
+    rounding: Rounding,
+) -> Result<T, FixedPointError> {
+    if x0 >= x1 {
+        return Err(FixedPointError::InvalidInput);
+    }
+    let x = x.max(x0).min(x1);
+    let width = checked_sub(x1, x0)?;
+    // measure the distance from the endpoint with the lower y, so the deltas are never
+    // negative
+    let (y_low, y_high, distance) = if y0 <= y1 {
+        (y0, y1, checked_sub(x, x0)?)
+    } else {
+        (y1, y0, checked_sub(x1, x)?)
+    };
+    let height = checked_sub(y_high, y_low)?;
+
+    // y rounded in the direction of the delta's rounding, measured up from y_low or
+    // down from y_high. Both deltas are at most the height, so neither can overflow.
+    let up = |rounding| {
+        let delta = height.try_fixed_mul(distance, width, rounding)?;
+        y_low.checked_add(delta).ok_or(FixedPointError::Overflow)
+    };
+    let down = |rounding| {
+        let delta = height.try_fixed_mul(checked_sub(width, distance)?, width, rounding)?;
+        y_high.checked_sub(delta).ok_or(FixedPointError::Overflow)
+    };
+    match rounding {
+        Rounding::Floor => up(Rounding::Floor),
+        Rounding::Ceil => up(Rounding::Ceil),
+        Rounding::TowardZero | Rounding::AwayFromZero => {
+            // y is negative exactly when its floor is
+            let floor = up(Rounding::Floor)?;
+            match (rounding, floor < T::ZERO) {
+                (Rounding::TowardZero, false) | (Rounding::AwayFromZero, true) => Ok(floor),
+                _ => up(Rounding::Ceil),
+            }
+        }
+        // ties round up from y_low and down from y_high, so the results only differ
+        // if y is halfway between them
+        Rounding::HalfUp | Rounding::HalfEven => {
+            let (tie_up, tie_down) = (up(Rounding::HalfUp)?, down(Rounding::HalfUp)?);
+            let two = T::ONE + T::ONE;
+            match rounding {
+                _ if tie_up == tie_down => Ok(tie_up),
+                Rounding::HalfUp if tie_down < T::ZERO => Ok(tie_down),
+                Rounding::HalfEven if tie_up % two != T::ZERO => Ok(tie_down),
+                _ => Ok(tie_up),
+            }
+        }
+    }
+}
+
+/// Safely calculates y at `x` on the line through (x0, y0) and (x1, y1), rounded in the
+/// direction of `rounding`. See `try_lerp` for details.
+///
+/// Returns None if x0 is not less than x1, or if the difference between the endpoints
+/// overflows.
+#[allow(deprecated)]
+pub fn lerp<T: FixedPoint + Integer>(
+    x0: T,
+    y0: T,
+    x1: T,
+    y1: T,
+    x: T,
+    rounding: Rounding,
+) -> Option<T> {
+    try_lerp(x0, y0, x1, y1, x, rounding).ok()
+}
+
+/// A curve through a set of (x, y) breakpoints, linearly interpolated between them
+///
+/// The curve is flat before the first and after the last breakpoint.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PiecewiseLinear<'a, T> {
+    points: &'a [(T, T)],
+}
+
+#[allow(deprecated)]
+impl<'a, T: FixedPoint + Integer> PiecewiseLinear<'a, T> {
+    /// Safely creates a curve from (x, y) breakpoints. Returns an error if there are no
+    /// breakpoints, or if their x values are not strictly increasing.
+    pub fn try_new(points: &'a [(T, T)]) -> Result<Self, FixedPointError> {
+        if points.is_empty() || points.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+            return Err(FixedPointError::InvalidInput);
+        }
+        Ok(PiecewiseLinear { points })
+    }
+
+    /// Safely creates a curve from (x, y) breakpoints. Returns None if there are no
+    /// breakpoints, or if their x values are not strictly increasing.
+    pub fn new(points: &'a [(T, T)]) -> Option<Self> {
+        Self::try_new(points).ok()
+    }
+
+    /// Returns the breakpoints of the curve
+    pub fn points(&self) -> &'a [(T, T)] {
+        self.points
+    }
+
+    /// Safely calculates y at `x`, rounded in the direction of `rounding`. See
+    /// `try_lerp` for how the rounding is applied. Returns an error if the difference
+    /// between two breakpoints does not fit in `T`.
+    pub fn try_evaluate(&self, x: T, rounding: Rounding) -> Result<T, FixedPointError> {
+        // the index of the first breakpoint after x
+        let index = self.points.partition_point(|point| point.0 <= x);
+        match (index.checked_sub(1), self.points.get(index)) {
+            (Some(start), Some(&(x1, y1))) => {
+                let (x0, y0) = self.points[start];
+                try_lerp(x0, y0, x1, y1, x, rounding)
+            }
+            (Some(start), None) => Ok(self.points[start].1),
+            (None, Some(&(_, y))) => Ok(y),
+            // the curve has at least one breakpoint
+            (None, None) => Err(FixedPointError::InvalidInput),
+        }
+    }
+
+    /// Safely calculates y at `x`, rounded in the direction of `rounding`. Returns None
+    /// if the difference between two breakpoints overflows.
+    pub fn evaluate(&self, x: T, rounding: Rounding) -> Option<T> {
+        self.try_evaluate(x, rounding).ok()
+    }
+}
+
+fn checked_sub<T: Integer>(x: T, y: T) -> Result<T, FixedPointError> {
+    x.checked_sub(y).ok_or(FixedPointError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lerp() {
+        // 100 + (250 - 100) * 1 / 3 = 150
+        assert_eq!(lerp(0_i128, 100, 3, 250, 1, Rounding::Floor), Some(150));
+        assert_eq!(lerp(0_i128, 100, 3, 250, 3, Rounding::Floor), Some(250));
+        // 10 + 10 * 1 / 3 = 13.333...
+        assert_eq!(lerp(0_u64, 10, 3, 20, 1, Rounding::Floor), Some(13));
+        assert_eq!(lerp(0_u64, 10, 3, 20, 1, Rounding::Ceil), Some(14));
+    }
+
+    #[test]
+    fn test_lerp_decreasing() {
+        // 20 - 10 * 1 / 3 = 16.666...
+        assert_eq!(lerp(0_u64, 20, 3, 10, 1, Rounding::Floor), Some(16));
+        assert_eq!(lerp(0_u64, 20, 3, 10, 1, Rounding::Ceil), Some(17));
+        assert_eq!(lerp(0_u64, 20, 3, 10, 3, Rounding::Ceil), Some(10));
+        // -5 + (-15 + 5) * 2 / 3 = -11.666...
+        assert_eq!(lerp(-10_i32, -5, -7, -15, -8, Rounding::Floor), Some(-12));
+        assert_eq!(lerp(-10_i32, -5, -7, -15, -8, Rounding::Ceil), Some(-11));
+    }
+
+    #[test]
+    fn test_lerp_negative() {
+        // -20 + 10 * 1 / 3 = -16.666...
+        assert_eq!(lerp(0, -20, 3, -10, 1, Rounding::TowardZero), Some(-16));
+        assert_eq!(lerp(0, -20, 3, -10, 1, Rounding::AwayFromZero), Some(-17));
+        assert_eq!(lerp(0, -20, 3, -10, 1, Rounding::HalfUp), Some(-17));
+        assert_eq!(lerp(0, -10, 3, -20, 2, Rounding::TowardZero), Some(-16));
+        // -20 + 10 * 1 / 4 = -17.5
+        assert_eq!(lerp(0, -20, 4, -10, 1, Rounding::HalfUp), Some(-18));
+        assert_eq!(lerp(0, -20, 4, -10, 1, Rounding::HalfEven), Some(-18));
+        assert_eq!(lerp(0, -10, 4, -20, 3, Rounding::HalfUp), Some(-18));
+        assert_eq!(lerp(0, -20, 4, -10, 1, Rounding::Ceil), Some(-17));
+    }
+
+    #[test]
+    fn test_lerp_nearest() {
+        // 3 + 1 * 1 / 2 = 3.5, and 2 + 1 * 1 / 2 = 2.5
+        assert_eq!(lerp(0_u64, 3, 2, 4, 1, Rounding::HalfEven), Some(4));
+        assert_eq!(lerp(0_u64, 2, 2, 3, 1, Rounding::HalfEven), Some(2));
+        assert_eq!(lerp(0_u64, 4, 2, 3, 1, Rounding::HalfEven), Some(4));
+        // -2 + 3 * 1 / 2 = -0.5, and -1 + 3 * 1 / 2 = 0.5
+        assert_eq!(lerp(0, -2, 2, 1, 1, Rounding::HalfUp), Some(-1));
+        assert_eq!(lerp(0, -2, 2, 1, 1, Rounding::HalfEven), Some(0));
+        assert_eq!(lerp(0, -2, 2, 1, 1, Rounding::TowardZero), Some(0));
+        assert_eq!(lerp(0, -1, 2, 2, 1, Rounding::HalfUp), Some(1));
+        assert_eq!(lerp(0, -1, 2, 2, 1, Rounding::AwayFromZero), Some(1));
+        assert_eq!(lerp(0, -1, 2, 2, 1, Rounding::TowardZero), Some(0));
+    }
+
+    #[test]
+    fn test_lerp_clamps() {
+        assert_eq!(lerp(10_u64, 20, 30, 40, 0, Rounding::Floor), Some(20));
+        assert_eq!(lerp(10_u64, 20, 30, 40, 31, Rounding::Floor), Some(40));
+        assert_eq!(
+            lerp(i128::MIN, 0, i128::MAX, 1, i128::MAX, Rounding::Floor),
+            None
+        );
+    }
+
+    #[test]
+    fn test_lerp_large_values() {
+        let max = i128::MAX;
+
+        assert_eq!(
+            lerp(0, 0, max, max, max / 2, Rounding::Floor),
+            Some(max / 2)
+        );
+        assert_eq!(
+            lerp(0, max, max, 0, max / 2, Rounding::Ceil),
+            Some(max / 2 + 1)
+        );
+    }
+
+    #[test]
+    fn test_lerp_errors() {
+        assert_eq!(
+            try_lerp(1_i128, 0, 1, 10, 1, Rounding::Floor),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_lerp(2_i128, 0, 1, 10, 1, Rounding::Floor),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_lerp(0_i128, i128::MIN, 1, i128::MAX, 1, Rounding::Floor),
+            Err(FixedPointError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_vesting_schedule() {
+        // nothing vests before the cliff at 100, then 25% vests and the rest vests
+        // linearly until 400
+        let points: [(u64, u64); 4] = [(0, 0), (99, 0), (100, 250), (400, 1000)];
+        let schedule = PiecewiseLinear::new(&points).unwrap();
+
+        assert_eq!(schedule.evaluate(0, Rounding::Floor), Some(0));
+        assert_eq!(schedule.evaluate(99, Rounding::Floor), Some(0));
+        assert_eq!(schedule.evaluate(100, Rounding::Floor), Some(250));
+        // 250 + 750 * 101 / 300 = 502.5
+        assert_eq!(schedule.evaluate(201, Rounding::Floor), Some(502));
+        assert_eq!(schedule.evaluate(201, Rounding::HalfUp), Some(503));
+        assert_eq!(schedule.evaluate(400, Rounding::Floor), Some(1000));
+        assert_eq!(schedule.evaluate(u64::MAX, Rounding::Floor), Some(1000));
+    }
+
+    #[test]
+    fn test_auction_decay() {
+        // the price starts at 2 and decays to 1 after 100 ledgers, and to 0.5 after 200
+        let points: [(i128, i128); 3] = [(1000, 2_0000000), (1100, 1_0000000), (1200, 5000000)];
+        let curve = PiecewiseLinear::new(&points).unwrap();
+
+        assert_eq!(curve.evaluate(0, Rounding::Ceil), Some(2_0000000));
+        assert_eq!(curve.evaluate(1033, Rounding::Ceil), Some(1_6700000));
+        // 1 - 0.5 * 33 / 100 = 0.835, and 1 - 0.5 * 1 / 3 = 0.8333...
+        assert_eq!(curve.evaluate(1133, Rounding::Ceil), Some(8350000));
+        assert_eq!(
+            PiecewiseLinear::new(&[(0_i128, 1_0000000), (3, 5000000)])
+                .unwrap()
+                .evaluate(1, Rounding::Ceil),
+            Some(8333334)
+        );
+        assert_eq!(curve.evaluate(5000, Rounding::Ceil), Some(5000000));
+    }
+
+    #[test]
+    fn test_single_point() {
+        let points = [(10_i128, 42)];
+        let curve = PiecewiseLinear::new(&points).unwrap();
+
+        assert_eq!(curve.evaluate(i128::MIN, Rounding::Floor), Some(42));
+        assert_eq!(curve.evaluate(10, Rounding::Floor), Some(42));
+        assert_eq!(curve.evaluate(i128::MAX, Rounding::Floor), Some(42));
+    }
+
+    #[test]
+    fn test_invalid_points() {
+        assert_eq!(
+            PiecewiseLinear::<i128>::try_new(&[]),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            PiecewiseLinear::try_new(&[(0_i128, 0), (1, 1), (1, 2)]),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            PiecewiseLinear::try_new(&[(2_i128, 0), (1, 1)]),
+            Err(FixedPointError::InvalidInput)
+        );
+        let points = [(i128::MIN, 0_i128), (i128::MAX, 1)];
+        assert_eq!(
+            PiecewiseLinear::new(&points)
+                .unwrap()
+                .try_evaluate(0, Rounding::Floor),
+            Err(FixedPointError::Overflow)
+        );
+    }
+}
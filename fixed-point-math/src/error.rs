@@ -0,0 +1,23 @@
+/// The reason a fixed-point operation failed
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum FixedPointError {
+    /// The operation divided by zero
+    DivideByZero = 1,
+    /// The result is greater than the maximum value of the type
+    Overflow = 2,
+    /// An intermediate value overflowed the widened type used to compute it, even
+    /// though the result may be within bounds
+    ///
+    /// Multiplication and division of two values are computed in a type that holds the
+    /// full product, so they never return this. Only operations that accumulate many
+    /// values, like `aggregate::try_fixed_dot`, can overflow an intermediate value.
+    PhantomOverflow = 3,
+    /// The result is less than the minimum value of the type
+    Underflow = 4,
+    /// The input is outside of the domain of the operation, like the logarithm of a
+    /// negative number
+    InvalidInput = 5,
+    /// An iterative solver did not converge within its maximum number of iterations
+    NoConvergence = 6,
+}
@@ -0,0 +1,282 @@
+#[allow(deprecated)]
+use crate::{
+    error::FixedPointError,
+    fixed_point::FixedPoint,
+    rounding::{Remainder, Rounding},
+    u256::U256,
+};
+
+/// The scale used for intermediate logarithm and exponential values (36 decimals)
+pub(crate) const PRECISION: i128 = 1_000_000_000_000_000_000_000_000_000_000_000_000;
+
+/// ln(2) at `PRECISION`, rounded down
+const LN_2: i128 = 693_147_180_559_945_309_417_232_121_458_176_568;
+
+/// Calculates ln(x / scale) at `PRECISION`
+///
+/// The result is within 1e-33 of the exact value.
+pub(crate) fn ln(x: i128, scale: i128) -> Result<i128, FixedPointError> {
+    check_scale(scale)?;
+    if x <= 0 {
+        return Err(FixedPointError::InvalidInput);
+    }
+    let (x_exp, x_mantissa) = split_log2(x)?;
+    let (scale_exp, scale_mantissa) = split_log2(scale)?;
+    // ln(x / scale) = (x_exp - scale_exp) * ln(2) + ln(x_mantissa) - ln(scale_mantissa)
+    Ok(
+        i128::from(x_exp - scale_exp) * LN_2 + ln_mantissa(x_mantissa)?
+            - ln_mantissa(scale_mantissa)?,
+    )
+}
+
+/// Calculates e^(x / PRECISION), where x is at `PRECISION`, and returns the result at
+/// `scale`, rounded to the nearest value
+///
+/// The relative error of the result before rounding is below 1e-33.
+#[allow(deprecated)]
+pub(crate) fn exp(x: i128, scale: i128) -> Result<i128, FixedPointError> {
+    check_scale(scale)?;
+    // e^x = 2^k * e^r, where 0 <= r < ln(2)
+    let k = x.div_euclid(LN_2);
+    let r = x.rem_euclid(LN_2);
+
+    // e^r = 1 + r + r^2 / 2! + r^3 / 3! + ...
+    let mut term = PRECISION;
+    let mut sum = PRECISION;
+    let mut n: i128 = 1;
+    while term > 0 {
+        term = term.try_fixed_mul_floor(r, PRECISION)? / n;
+        sum += term;
+        n += 1;
+    }
+
+    // scale the result to sum * 2^k * scale / PRECISION in a single rounding
+    let mut numerator = U256::mul_u128(sum as u128, scale as u128);
+    if k >= 0 {
+        let shift = u32::try_from(k).map_err(|_| FixedPointError::Overflow)?;
+        numerator = numerator
+            .checked_shl(shift)
+            .ok_or(FixedPointError::Overflow)?;
+    } else {
+        let shift = u32::try_from(-k).unwrap_or(u32::MAX);
+        numerator = numerator.shr(shift);
+    }
+    let (result, remainder) = numerator
+        .div_rem_u128(PRECISION as u128)
+        .ok_or(FixedPointError::DivideByZero)?;
+    let mut result = result.to_u128().ok_or(FixedPointError::Overflow)?;
+    if Rounding::HalfUp.round_away_from_zero(
+        false,
+        result % 2 == 1,
+        Remainder::new(remainder, PRECISION as u128),
+    ) {
+        result += 1;
+    }
+    i128::try_from(result).map_err(|_| FixedPointError::Overflow)
+}
+
+/// Calculates ln(x / scale) at `scale`, rounded to the nearest value
+#[allow(deprecated)]
+pub(crate) fn fixed_ln(x: i128, scale: i128) -> Result<i128, FixedPointError> {
+    ln(x, scale)?.try_fixed_mul(scale, PRECISION, Rounding::HalfUp)
+}
+
+/// Calculates log2(x / scale) at `scale`, rounded to the nearest value
+#[allow(deprecated)]
+pub(crate) fn fixed_log2(x: i128, scale: i128) -> Result<i128, FixedPointError> {
+    // log2(x) = ln(x) / ln(2)
+    ln(x, scale)?.try_fixed_mul(scale, LN_2, Rounding::HalfUp)
+}
+
+/// Calculates e^(x / scale) at `scale`, rounded to the nearest value
+#[allow(deprecated)]
+pub(crate) fn fixed_exp(x: i128, scale: i128) -> Result<i128, FixedPointError> {
+    check_scale(scale)?;
+    match x.try_fixed_mul_floor(PRECISION, scale) {
+        Ok(exponent) => exp(exponent, scale),
+        // x / scale < -170, so e^x rounds to 0 at any supported scale
+        Err(FixedPointError::Underflow) => Ok(0),
+        Err(err) => Err(err),
+    }
+}
+
+/// Calculates the APY of `apr` compounded `periods` times per year, as
+/// (1 + apr / periods)^periods - 1 at `scale`, rounded to the nearest value
+pub(crate) fn apr_to_apy(apr: i128, periods: i128, scale: i128) -> Result<i128, FixedPointError> {
+    check_scale(scale)?;
+    let periodic_scale = check_periods(periods)?
+        .checked_mul(scale)
+        .ok_or(FixedPointError::Overflow)?;
+    // (1 + apr / periods)^periods = e^(periods * ln((periods * scale + apr) / (periods * scale)))
+    let x = periodic_scale
+        .checked_add(apr)
+        .ok_or(FixedPointError::Overflow)?;
+    let exponent = ln(x, periodic_scale)?
+        .checked_mul(periods)
+        .ok_or(FixedPointError::Overflow)?;
+    Ok(exp(exponent, scale)? - scale)
+}
+
+/// Calculates the APR that compounds to `apy` when compounded `periods` times per
+/// year, as periods * ((1 + apy)^(1 / periods) - 1) at `scale`, rounded to the nearest
+/// value
+#[allow(deprecated)]
+pub(crate) fn apy_to_apr(apy: i128, periods: i128, scale: i128) -> Result<i128, FixedPointError> {
+    check_scale(scale)?;
+    let periods = check_periods(periods)?;
+    let x = scale.checked_add(apy).ok_or(FixedPointError::Overflow)?;
+    let exponent = ln(x, scale)?.try_fixed_mul(1, periods, Rounding::HalfUp)?;
+    // keep the periodic rate at `PRECISION`, as it is much smaller than the APR
+    let periodic_rate = exp(exponent, PRECISION)? - PRECISION;
+    let periodic_scale = periods
+        .checked_mul(scale)
+        .ok_or(FixedPointError::Overflow)?;
+    periodic_rate.try_fixed_mul(periodic_scale, PRECISION, Rounding::HalfUp)
+}
+
+/// Calculates (x / scale)^(y / scale) as e^(y * ln(x)), returned at `scale`
+#[allow(deprecated)]
+pub(crate) fn pow(x: i128, y: i128, scale: i128) -> Result<i128, FixedPointError> {
+    check_scale(scale)?;
+    if x < 0 {
+        return Err(FixedPointError::InvalidInput);
+    }
+    if x == 0 {
+        return match y {
+            y if y > 0 => Ok(0),
+            0 => Ok(scale),
+            _ => Err(FixedPointError::DivideByZero),
+        };
+    }
+    match y.try_fixed_mul_floor(ln(x, scale)?, scale) {
+        Ok(exponent) => exp(exponent, scale),
+        // e^exponent is too small to be represented at any supported scale
+        Err(FixedPointError::Underflow) => Ok(0),
+        Err(err) => Err(err),
+    }
+}
+
+fn check_scale(scale: i128) -> Result<(), FixedPointError> {
+    match scale {
+        0 => Err(FixedPointError::DivideByZero),
+        scale if scale < 0 => Err(FixedPointError::InvalidInput),
+        _ => Ok(()),
+    }
+}
+
+fn check_periods(periods: i128) -> Result<i128, FixedPointError> {
+    if periods <= 0 {
+        return Err(FixedPointError::InvalidInput);
+    }
+    Ok(periods)
+}
+
+/// Splits a positive value into `k` and `m`, such that value = 2^k * m / PRECISION,
+/// where PRECISION <= m < 2 * PRECISION
+#[allow(deprecated)]
+fn split_log2(value: i128) -> Result<(i32, i128), FixedPointError> {
+    let k = 127 - value.leading_zeros();
+    let mantissa = value.try_fixed_mul_floor(PRECISION, 1 << k)?;
+    Ok((k as i32, mantissa))
+}
+
+/// Calculates ln(m) at `PRECISION`, where PRECISION <= m < 2 * PRECISION
+#[allow(deprecated)]
+fn ln_mantissa(m: i128) -> Result<i128, FixedPointError> {
+    // ln(m) = 2 * atanh(z) = 2 * (z + z^3 / 3 + z^5 / 5 + ...), where z = (m - 1) / (m + 1)
+    // and 0 <= z < 1/3
+    let z = (m - PRECISION).try_fixed_div_floor(m + PRECISION, PRECISION)?;
+    let z_squared = z.try_fixed_mul_floor(z, PRECISION)?;
+    let mut term = z;
+    let mut sum = z;
+    let mut n: i128 = 1;
+    while term > 0 {
+        term = term.try_fixed_mul_floor(z_squared, PRECISION)?;
+        n += 2;
+        sum += term / n;
+    }
+    Ok(2 * sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ln() {
+        let result = ln(2_0000000, 1_0000000).unwrap();
+
+        assert!((result - LN_2).abs() <= 100);
+    }
+
+    #[test]
+    fn test_ln_large_number() {
+        // ln(10^20) = 46.051701859880913680359829093687284152...
+        let result = ln(i128::pow(10, 38), i128::pow(10, 18)).unwrap();
+
+        let expected = 46_051_701_859_880_913_680_359_829_093_687_284_152;
+        assert!((result - expected).abs() <= 100);
+    }
+
+    #[test]
+    fn test_ln_less_than_one() {
+        // ln(0.0001234) = -9.000079446492986664711136382722182060...
+        let result = ln(1234, 1_0000000).unwrap();
+
+        let expected = -9_000_079_446_492_986_664_711_136_382_722_182_060;
+        assert!((result - expected).abs() <= 100);
+    }
+
+    #[test]
+    fn test_ln_invalid_input() {
+        assert_eq!(ln(0, 1_0000000), Err(FixedPointError::InvalidInput));
+        assert_eq!(ln(-1, 1_0000000), Err(FixedPointError::InvalidInput));
+        assert_eq!(ln(1, 0), Err(FixedPointError::DivideByZero));
+    }
+
+    #[test]
+    fn test_fixed_log2_power_of_two() {
+        assert_eq!(fixed_log2(8_0000000, 1_0000000), Ok(3_0000000));
+        assert_eq!(fixed_log2(1, 1_000_000_000), Ok(-29_897_352_854));
+        assert_eq!(fixed_log2(1_0000000, 1_0000000), Ok(0));
+    }
+
+    #[test]
+    fn test_exp() {
+        // e = 2.718281828459045235360287471352662497...
+        let result = exp(PRECISION, 1_000_000_000_000_000_000).unwrap();
+
+        assert_eq!(result, 2_718_281_828_459_045_235);
+    }
+
+    #[test]
+    fn test_exp_negative() {
+        // e^-10 = 0.000045399929762484851535591515560550...
+        let result = exp(-10 * PRECISION, 1_000_000_000_000_000_000).unwrap();
+
+        assert_eq!(result, 45_399_929_762_485);
+    }
+
+    #[test]
+    fn test_exp_large_number() {
+        // e^80 = 55406223843935100525711733958316612.92...
+        let result = exp(80 * PRECISION, 1).unwrap();
+
+        let expected = 55_406_223_843_935_100_525_711_733_958_316_613;
+        assert!((result - expected).abs() <= 100);
+    }
+
+    #[test]
+    fn test_exp_overflow() {
+        let result = exp(100 * PRECISION, 1_0000000);
+
+        assert_eq!(result, Err(FixedPointError::Overflow));
+    }
+
+    #[test]
+    fn test_exp_rounds_to_zero() {
+        let result = exp(-100 * PRECISION, 1_0000000).unwrap();
+
+        assert_eq!(result, 0);
+    }
+}
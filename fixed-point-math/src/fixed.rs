@@ -0,0 +1,336 @@
+use core::{fmt, str::FromStr};
+
+#[allow(deprecated)]
+use crate::{
+    error::FixedPointError,
+    fixed_point::FixedPoint,
+    format::{self, FixedDisplay},
+    integer::Integer,
+    rounding::Rounding,
+};
+
+/// A fixed-point number with `DECIMALS` decimal places, stored as the raw integer
+/// `T` scaled by `10^DECIMALS`.
+///
+/// Values of different scales are different types, so they cannot be combined
+/// by accident. Multiplication and division require the rounding direction to be
+/// picked explicitly. There are no arithmetic operators, since every operation
+/// can overflow, so use the `try_` and `checked_` methods instead.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed<T, const DECIMALS: u32>(T);
+
+#[allow(deprecated)]
+impl<T: FixedPoint + Integer, const DECIMALS: u32> Fixed<T, DECIMALS> {
+    /// Creates a fixed-point number from a raw value that is already scaled by
+    /// `10^DECIMALS`
+    pub const fn from_raw(raw: T) -> Self {
+        Fixed(raw)
+    }
+
+    /// Returns the raw value, scaled by `10^DECIMALS`
+    pub fn to_raw(self) -> T {
+        self.0
+    }
+
+    /// Returns the raw value representing 1, or None if `10^DECIMALS` does not fit in `T`
+    pub fn scale() -> Option<T> {
+        T::TEN.checked_pow(DECIMALS)
+    }
+
+    /// Returns the fixed-point number representing 1, or None if it does not fit in `T`
+    pub fn one() -> Option<Self> {
+        Self::scale().map(Fixed)
+    }
+
+    /// Creates a fixed-point number from a whole number. Returns None if the scaled
+    /// value overflows.
+    pub fn from_integer(value: T) -> Option<Self> {
+        value.checked_mul(Self::scale()?).map(Fixed)
+    }
+
+    /// Safely calculates self + rhs. Returns an error if the result does not fit in `T`.
+    pub fn try_add(self, rhs: Self) -> Result<Self, FixedPointError> {
+        match self.0.checked_add(rhs.0) {
+            Some(result) => Ok(Fixed(result)),
+            None if rhs.0 < T::ZERO => Err(FixedPointError::Underflow),
+            None => Err(FixedPointError::Overflow),
+        }
+    }
+
+    /// Safely calculates self - rhs. Returns an error if the result does not fit in `T`.
+    pub fn try_sub(self, rhs: Self) -> Result<Self, FixedPointError> {
+        match self.0.checked_sub(rhs.0) {
+            Some(result) => Ok(Fixed(result)),
+            None if rhs.0 < T::ZERO => Err(FixedPointError::Overflow),
+            None => Err(FixedPointError::Underflow),
+        }
+    }
+
+    /// Safely calculates -self. Returns an error if the result does not fit in `T`.
+    pub fn try_neg(self) -> Result<Self, FixedPointError> {
+        match T::ZERO.checked_sub(self.0) {
+            Some(result) => Ok(Fixed(result)),
+            None if self.0 < T::ZERO => Err(FixedPointError::Overflow),
+            None => Err(FixedPointError::Underflow),
+        }
+    }
+
+    /// Safely calculates self * rhs, rounded in the direction of `rounding`. Returns
+    /// an error if the result does not fit in `T`.
+    pub fn try_mul(self, rhs: Self, rounding: Rounding) -> Result<Self, FixedPointError> {
+        let scale = Self::scale().ok_or(FixedPointError::Overflow)?;
+        self.0.try_fixed_mul(rhs.0, scale, rounding).map(Fixed)
+    }
+
+    /// Safely calculates self / rhs, rounded in the direction of `rounding`. Returns
+    /// an error if the result does not fit in `T` or if rhs is 0.
+    pub fn try_div(self, rhs: Self, rounding: Rounding) -> Result<Self, FixedPointError> {
+        let scale = Self::scale().ok_or(FixedPointError::Overflow)?;
+        self.0.try_fixed_div(rhs.0, scale, rounding).map(Fixed)
+    }
+
+    /// Safely converts the value to `TO` decimal places. Digits dropped when scaling
+    /// down are rounded in the direction of `rounding`. Returns an error if the result
+    /// does not fit in `T`.
+    pub fn try_rescale<const TO: u32>(
+        self,
+        rounding: Rounding,
+    ) -> Result<Fixed<T, TO>, FixedPointError> {
+        self.0.try_rescale(DECIMALS, TO, rounding).map(Fixed)
+    }
+
+    /// Safely converts the value to `TO` decimal places. Digits dropped when scaling
+    /// down are rounded in the direction of `rounding`. Returns None if an overflow
+    /// occurs.
+    pub fn rescale<const TO: u32>(self, rounding: Rounding) -> Option<Fixed<T, TO>> {
+        self.try_rescale(rounding).ok()
+    }
+
+    /// Safely calculates self + rhs. Returns None if an overflow occurs.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.try_add(rhs).ok()
+    }
+
+    /// Safely calculates self - rhs. Returns None if an overflow occurs.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.try_sub(rhs).ok()
+    }
+
+    /// Safely calculates -self. Returns None if an overflow occurs.
+    pub fn checked_neg(self) -> Option<Self> {
+        self.try_neg().ok()
+    }
+
+    /// Safely calculates self * rhs, rounded in the direction of `rounding`. Returns
+    /// None if an overflow occurs.
+    pub fn checked_mul(self, rhs: Self, rounding: Rounding) -> Option<Self> {
+        self.try_mul(rhs, rounding).ok()
+    }
+
+    /// Safely calculates self / rhs, rounded in the direction of `rounding`. Returns
+    /// None if an overflow occurs or if rhs is 0.
+    pub fn checked_div(self, rhs: Self, rounding: Rounding) -> Option<Self> {
+        self.try_div(rhs, rounding).ok()
+    }
+
+    /// Safely calculates floor(self * rhs). Returns None if an overflow occurs.
+    pub fn checked_mul_floor(self, rhs: Self) -> Option<Self> {
+        self.checked_mul(rhs, Rounding::Floor)
+    }
+
+    /// Safely calculates ceil(self * rhs). Returns None if an overflow occurs.
+    pub fn checked_mul_ceil(self, rhs: Self) -> Option<Self> {
+        self.checked_mul(rhs, Rounding::Ceil)
+    }
+
+    /// Safely calculates floor(self / rhs). Returns None if an overflow occurs or
+    /// if rhs is 0.
+    pub fn checked_div_floor(self, rhs: Self) -> Option<Self> {
+        self.checked_div(rhs, Rounding::Floor)
+    }
+
+    /// Safely calculates ceil(self / rhs). Returns None if an overflow occurs or
+    /// if rhs is 0.
+    pub fn checked_div_ceil(self, rhs: Self) -> Option<Self> {
+        self.checked_div(rhs, Rounding::Ceil)
+    }
+}
+
+impl<T: Integer, const DECIMALS: u32> fmt::Display for Fixed<T, DECIMALS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        FixedDisplay::new(self.0, DECIMALS).fmt(f)
+    }
+}
+
+impl<T: Integer, const DECIMALS: u32> FromStr for Fixed<T, DECIMALS> {
+    type Err = FixedPointError;
+
+    /// Parses a decimal string, such as `-1.5`. Strings with nonzero fractional digits
+    /// beyond `DECIMALS` are rejected.
+    fn from_str(s: &str) -> Result<Self, FixedPointError> {
+        format::parse(s, DECIMALS, None).map(Fixed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Stroop = Fixed<i128, 7>;
+
+    #[test]
+    fn test_from_integer() {
+        let result = Stroop::from_integer(3).unwrap();
+
+        assert_eq!(result.to_raw(), 3_0000000);
+        assert_eq!(Stroop::one().unwrap().to_raw(), 1_0000000);
+    }
+
+    #[test]
+    fn test_from_integer_overflow() {
+        let result = Fixed::<u32, 7>::from_integer(430);
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_scale_overflow() {
+        let result = Fixed::<u64, 20>::one();
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_add_sub() {
+        let x = Stroop::from_raw(1_5000000);
+        let y = Stroop::from_raw(2_2500000);
+
+        assert_eq!(x.checked_add(y).unwrap().to_raw(), 3_7500000);
+        assert_eq!(x.checked_sub(y).unwrap().to_raw(), -7500000);
+        assert_eq!(x.checked_neg().unwrap().to_raw(), -1_5000000);
+    }
+
+    #[test]
+    fn test_checked_add_overflow() {
+        let x = Stroop::from_raw(i128::MAX);
+        let y = Stroop::from_raw(1);
+
+        assert_eq!(x.checked_add(y), None);
+        assert_eq!(
+            x.checked_neg().unwrap().checked_sub(y).unwrap().to_raw(),
+            i128::MIN
+        );
+    }
+
+    #[test]
+    fn test_checked_mul_rounds() {
+        let x = Stroop::from_raw(1_5391283);
+        let y = Stroop::from_raw(314_1592653);
+
+        assert_eq!(x.checked_mul_floor(y).unwrap().to_raw(), 483_5314159);
+        assert_eq!(x.checked_mul_ceil(y).unwrap().to_raw(), 483_5314160);
+    }
+
+    #[test]
+    fn test_checked_div_rounds() {
+        let x = Stroop::from_raw(314_1592653);
+        let y = Stroop::from_raw(1_5391280);
+
+        assert_eq!(x.checked_div_floor(y).unwrap().to_raw(), 204_1150997);
+        assert_eq!(x.checked_div_ceil(y).unwrap().to_raw(), 204_1150998);
+    }
+
+    #[test]
+    fn test_checked_mul_half_even() {
+        let x = Stroop::from_raw(2_5000000);
+        let y = Stroop::from_raw(1);
+
+        assert_eq!(x.checked_mul(y, Rounding::HalfEven).unwrap().to_raw(), 2);
+        assert_eq!(x.checked_mul(y, Rounding::HalfUp).unwrap().to_raw(), 3);
+    }
+
+    #[test]
+    fn test_checked_div_by_zero() {
+        let x = Fixed::<u64, 9>::from_raw(1_000_000_000);
+        let y = Fixed::<u64, 9>::from_raw(0);
+
+        assert_eq!(x.checked_div_floor(y), None);
+        assert_eq!(x.checked_div_ceil(y), None);
+    }
+
+    #[test]
+    fn test_try_errors() {
+        let max = Stroop::from_raw(i128::MAX);
+        let min = Stroop::from_raw(i128::MIN);
+        let one = Stroop::one().unwrap();
+        let zero = Stroop::default();
+
+        assert_eq!(max.try_add(one), Err(FixedPointError::Overflow));
+        assert_eq!(
+            min.try_add(one.try_neg().unwrap()),
+            Err(FixedPointError::Underflow)
+        );
+        assert_eq!(min.try_sub(one), Err(FixedPointError::Underflow));
+        assert_eq!(
+            max.try_sub(one.try_neg().unwrap()),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!(min.try_neg(), Err(FixedPointError::Overflow));
+        assert_eq!(
+            Fixed::<u64, 7>::from_raw(1).try_neg(),
+            Err(FixedPointError::Underflow)
+        );
+        assert_eq!(Fixed::<u64, 7>::default().try_neg(), Ok(Fixed::default()));
+        assert_eq!(
+            max.try_mul(min, Rounding::Floor),
+            Err(FixedPointError::Underflow)
+        );
+        assert_eq!(
+            one.try_div(zero, Rounding::Floor),
+            Err(FixedPointError::DivideByZero)
+        );
+        assert_eq!(
+            Fixed::<u64, 20>::from_raw(1).try_mul(Fixed::from_raw(1), Rounding::Floor),
+            Err(FixedPointError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_rescale() {
+        let x = Stroop::from_raw(1_2345678);
+
+        let wad: Fixed<i128, 18> = x.rescale(Rounding::Floor).unwrap();
+        let cents: Fixed<i128, 2> = x.rescale(Rounding::Ceil).unwrap();
+
+        assert_eq!(wad.to_raw(), 1_234_567_800_000_000_000);
+        assert_eq!(cents.to_raw(), 124);
+        assert_eq!(
+            Fixed::<u32, 7>::from_raw(u32::MAX).try_rescale::<9>(Rounding::Floor),
+            Err(FixedPointError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_display_from_str() {
+        extern crate std;
+        use std::string::ToString;
+
+        let x: Stroop = "-12.25".parse().unwrap();
+
+        assert_eq!(x.to_raw(), -12_2500000);
+        assert_eq!(x.to_string(), "-12.2500000");
+        assert_eq!(
+            "0.00000001".parse::<Stroop>(),
+            Err(FixedPointError::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn test_ordering() {
+        let x = Stroop::from_raw(1_5000000);
+        let y = Stroop::from_raw(-2_0000000);
+
+        assert!(x > y);
+        assert_eq!(x.max(y), x);
+    }
+}
@@ -1,18 +1,1440 @@
-#[deprecated(since="0.1.0", note="please use crate `soroban-fixed-point-math` instead")]
+use crate::{
+    error::FixedPointError,
+    exp_log,
+    format::{self, FixedDisplay},
+    integer::Integer,
+    mul_mul_div, rescale,
+    rounding::{Remainder, Rounding},
+    u256::U256,
+    STROOP_DECIMALS,
+};
+
+/// The denominator of a value in basis points
+pub(crate) const BPS_DENOMINATOR: i128 = 10_000;
+
+/// The denominator of a value in percent
+const PERCENT_DENOMINATOR: i128 = 100;
+
+/// Fixed-point multiplication and division for integers
+///
+/// Implementors only provide `try_fixed_mul`, and every other method is derived from it.
+#[deprecated(
+    since = "0.1.0",
+    note = "please use crate `soroban-fixed-point-math` instead"
+)]
 pub trait FixedPoint: Sized {
-    /// Safely calculates floor(x * y / denominator). Returns None if a phantom overflow
-    /// occurs or if the denominator is 0.
-    fn fixed_mul_floor(self, y: Self, denominator: Self) -> Option<Self>;
+    /// Safely calculates x * y / denominator, rounded in the direction of `rounding`.
+    /// Returns an error if the result does not fit in the type or if the denominator is 0.
+    fn try_fixed_mul(
+        self,
+        y: Self,
+        denominator: Self,
+        rounding: Rounding,
+    ) -> Result<Self, FixedPointError>;
+
+    /// Safely calculates x * denominator / y, rounded in the direction of `rounding`.
+    /// Returns an error if the result does not fit in the type or if y is 0.
+    fn try_fixed_div(
+        self,
+        y: Self,
+        denominator: Self,
+        rounding: Rounding,
+    ) -> Result<Self, FixedPointError> {
+        self.try_fixed_mul(denominator, y, rounding)
+    }
+
+    /// Safely calculates floor(x * y / denominator). Returns an error if the result
+    /// does not fit in the type or if the denominator is 0.
+    fn try_fixed_mul_floor(self, y: Self, denominator: Self) -> Result<Self, FixedPointError> {
+        self.try_fixed_mul(y, denominator, Rounding::Floor)
+    }
+
+    /// Safely calculates ceil(x * y / denominator). Returns an error if the result
+    /// does not fit in the type or if the denominator is 0.
+    fn try_fixed_mul_ceil(self, y: Self, denominator: Self) -> Result<Self, FixedPointError> {
+        self.try_fixed_mul(y, denominator, Rounding::Ceil)
+    }
+
+    /// Safely calculates floor(x * denominator / y). Returns an error if the result
+    /// does not fit in the type or if y is 0.
+    fn try_fixed_div_floor(self, y: Self, denominator: Self) -> Result<Self, FixedPointError> {
+        self.try_fixed_div(y, denominator, Rounding::Floor)
+    }
+
+    /// Safely calculates ceil(x * denominator / y). Returns an error if the result
+    /// does not fit in the type or if y is 0.
+    fn try_fixed_div_ceil(self, y: Self, denominator: Self) -> Result<Self, FixedPointError> {
+        self.try_fixed_div(y, denominator, Rounding::Ceil)
+    }
+
+    /// Safely calculates x * y / denominator, rounded in the direction of `rounding`.
+    /// Returns None if the result overflows or if the denominator is 0.
+    fn fixed_mul(self, y: Self, denominator: Self, rounding: Rounding) -> Option<Self> {
+        self.try_fixed_mul(y, denominator, rounding).ok()
+    }
+
+    /// Safely calculates x * denominator / y, rounded in the direction of `rounding`.
+    /// Returns None if the result overflows or if y is 0.
+    fn fixed_div(self, y: Self, denominator: Self, rounding: Rounding) -> Option<Self> {
+        self.try_fixed_div(y, denominator, rounding).ok()
+    }
+
+    /// Safely calculates floor(x * y / denominator). Returns None if the result
+    /// overflows or if the denominator is 0.
+    fn fixed_mul_floor(self, y: Self, denominator: Self) -> Option<Self> {
+        self.try_fixed_mul_floor(y, denominator).ok()
+    }
+
+    /// Safely calculates ceil(x * y / denominator). Returns None if the result
+    /// overflows or if the denominator is 0.
+    fn fixed_mul_ceil(self, y: Self, denominator: Self) -> Option<Self> {
+        self.try_fixed_mul_ceil(y, denominator).ok()
+    }
+
+    /// Safely calculates floor(x * denominator / y). Returns None if the result
+    /// overflows or if y is 0.
+    fn fixed_div_floor(self, y: Self, denominator: Self) -> Option<Self> {
+        self.try_fixed_div_floor(y, denominator).ok()
+    }
+
+    /// Safely calculates ceil(x * denominator / y). Returns None if the result
+    /// overflows or if y is 0.
+    fn fixed_div_ceil(self, y: Self, denominator: Self) -> Option<Self> {
+        self.try_fixed_div_ceil(y, denominator).ok()
+    }
+
+    /// Safely calculates x * y * z / (d1 * d2), rounded once in the direction of
+    /// `rounding`. Returns an error if the result does not fit in the type or if either
+    /// denominator is 0.
+    ///
+    /// This is more precise than two chained `fixed_mul` calls, such as converting an
+    /// amount through a price and then an exchange rate, as the intermediate values are
+    /// held in 256 bits and are never rounded.
+    fn try_mul_mul_div(
+        self,
+        y: Self,
+        z: Self,
+        d1: Self,
+        d2: Self,
+        rounding: Rounding,
+    ) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        mul_mul_div::mul_mul_div(self, y, z, d1, d2, rounding)
+    }
+
+    /// Safely calculates floor(x * y * z / (d1 * d2)). Returns an error if the result
+    /// does not fit in the type or if either denominator is 0.
+    fn try_mul_mul_div_floor(
+        self,
+        y: Self,
+        z: Self,
+        d1: Self,
+        d2: Self,
+    ) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        self.try_mul_mul_div(y, z, d1, d2, Rounding::Floor)
+    }
+
+    /// Safely calculates ceil(x * y * z / (d1 * d2)). Returns an error if the result
+    /// does not fit in the type or if either denominator is 0.
+    fn try_mul_mul_div_ceil(
+        self,
+        y: Self,
+        z: Self,
+        d1: Self,
+        d2: Self,
+    ) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        self.try_mul_mul_div(y, z, d1, d2, Rounding::Ceil)
+    }
+
+    /// Safely calculates x * y * z / (d1 * d2), rounded once in the direction of
+    /// `rounding`. Returns None if the result overflows or if either denominator is 0.
+    fn mul_mul_div(self, y: Self, z: Self, d1: Self, d2: Self, rounding: Rounding) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_mul_mul_div(y, z, d1, d2, rounding).ok()
+    }
+
+    /// Safely calculates floor(x * y * z / (d1 * d2)). Returns None if the result
+    /// overflows or if either denominator is 0.
+    fn mul_mul_div_floor(self, y: Self, z: Self, d1: Self, d2: Self) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_mul_mul_div_floor(y, z, d1, d2).ok()
+    }
+
+    /// Safely calculates ceil(x * y * z / (d1 * d2)). Returns None if the result
+    /// overflows or if either denominator is 0.
+    fn mul_mul_div_ceil(self, y: Self, z: Self, d1: Self, d2: Self) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_mul_mul_div_ceil(y, z, d1, d2).ok()
+    }
+
+    /// Calculates x * y / denominator, rounded in the direction of `rounding`. Results
+    /// that do not fit in the type are clamped to the type's minimum or maximum.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the denominator is 0, like integer division, or if the operation
+    /// fails for any other reason than the result being out of bounds.
+    fn saturating_fixed_mul(self, y: Self, denominator: Self, rounding: Rounding) -> Self
+    where
+        Self: Integer,
+    {
+        match self.try_fixed_mul(y, denominator, rounding) {
+            Ok(result) => result,
+            Err(FixedPointError::Overflow) => Self::MAX,
+            Err(FixedPointError::Underflow) => Self::MIN,
+            // the direction of a phantom overflow follows the sign of the operands
+            Err(FixedPointError::PhantomOverflow) => {
+                if (self < Self::ZERO) ^ (y < Self::ZERO) ^ (denominator < Self::ZERO) {
+                    Self::MIN
+                } else {
+                    Self::MAX
+                }
+            }
+            Err(FixedPointError::DivideByZero) => panic!("attempt to divide by zero"),
+            Err(error) => panic!("saturating fixed-point multiplication failed: {:?}", error),
+        }
+    }
+
+    /// Calculates x * denominator / y, rounded in the direction of `rounding`. Results
+    /// that do not fit in the type are clamped to the type's minimum or maximum.
+    ///
+    /// # Panics
+    ///
+    /// Panics if y is 0, like integer division.
+    fn saturating_fixed_div(self, y: Self, denominator: Self, rounding: Rounding) -> Self
+    where
+        Self: Integer,
+    {
+        self.saturating_fixed_mul(denominator, y, rounding)
+    }
+
+    /// Calculates floor(x * y / denominator), clamped to the type's minimum or maximum.
+    /// Panics if the denominator is 0.
+    fn saturating_fixed_mul_floor(self, y: Self, denominator: Self) -> Self
+    where
+        Self: Integer,
+    {
+        self.saturating_fixed_mul(y, denominator, Rounding::Floor)
+    }
+
+    /// Calculates ceil(x * y / denominator), clamped to the type's minimum or maximum.
+    /// Panics if the denominator is 0.
+    fn saturating_fixed_mul_ceil(self, y: Self, denominator: Self) -> Self
+    where
+        Self: Integer,
+    {
+        self.saturating_fixed_mul(y, denominator, Rounding::Ceil)
+    }
+
+    /// Calculates floor(x * denominator / y), clamped to the type's minimum or maximum.
+    /// Panics if y is 0.
+    fn saturating_fixed_div_floor(self, y: Self, denominator: Self) -> Self
+    where
+        Self: Integer,
+    {
+        self.saturating_fixed_div(y, denominator, Rounding::Floor)
+    }
+
+    /// Calculates ceil(x * denominator / y), clamped to the type's minimum or maximum.
+    /// Panics if y is 0.
+    fn saturating_fixed_div_ceil(self, y: Self, denominator: Self) -> Self
+    where
+        Self: Integer,
+    {
+        self.saturating_fixed_div(y, denominator, Rounding::Ceil)
+    }
+
+    /// Safely calculates x * bps / 10,000, rounded in the direction of `rounding`.
+    /// Returns an error if the result does not fit in the type.
+    fn try_apply_bps_rounded(self, bps: Self, rounding: Rounding) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        let denominator = Self::from_i128(BPS_DENOMINATOR).ok_or(FixedPointError::Overflow)?;
+        self.try_fixed_mul(bps, denominator, rounding)
+    }
+
+    /// Safely calculates x * bps / 10,000, rounded in the direction of `rounding`.
+    /// Returns None if the result overflows.
+    fn apply_bps_rounded(self, bps: Self, rounding: Rounding) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_apply_bps_rounded(bps, rounding).ok()
+    }
+
+    /// Safely calculates ceil(x * bps / 10,000), such that a fee charged by the
+    /// protocol is never rounded down. Returns an error if the result does not fit
+    /// in the type.
+    fn try_apply_bps(self, bps: Self) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        self.try_apply_bps_rounded(bps, Rounding::Ceil)
+    }
+
+    /// Safely calculates ceil(x * bps / 10,000), such that a fee charged by the
+    /// protocol is never rounded down. Returns None if the result overflows.
+    fn apply_bps(self, bps: Self) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_apply_bps(bps).ok()
+    }
+
+    /// Safely calculates x * percent / 100, rounded in the direction of `rounding`.
+    /// Returns an error if the result does not fit in the type.
+    fn try_apply_percent_rounded(
+        self,
+        percent: Self,
+        rounding: Rounding,
+    ) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        let denominator = Self::from_i128(PERCENT_DENOMINATOR).ok_or(FixedPointError::Overflow)?;
+        self.try_fixed_mul(percent, denominator, rounding)
+    }
+
+    /// Safely calculates x * percent / 100, rounded in the direction of `rounding`.
+    /// Returns None if the result overflows.
+    fn apply_percent_rounded(self, percent: Self, rounding: Rounding) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_apply_percent_rounded(percent, rounding).ok()
+    }
+
+    /// Safely calculates ceil(x * percent / 100), such that a fee charged by the
+    /// protocol is never rounded down. Returns an error if the result does not fit
+    /// in the type.
+    fn try_apply_percent(self, percent: Self) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        self.try_apply_percent_rounded(percent, Rounding::Ceil)
+    }
+
+    /// Safely calculates ceil(x * percent / 100), such that a fee charged by the
+    /// protocol is never rounded down. Returns None if the result overflows.
+    fn apply_percent(self, percent: Self) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_apply_percent(percent).ok()
+    }
+
+    /// Safely calculates the gross amount that leaves x after a fee of `fee_bps` is
+    /// taken from it, as x * 10,000 / (10,000 - fee_bps), rounded in the direction of
+    /// `rounding`. Returns an error if the fee is not between 0 and 9,999 bps, or if
+    /// the result does not fit in the type.
+    fn try_gross_up_for_fee_rounded(
+        self,
+        fee_bps: Self,
+        rounding: Rounding,
+    ) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        let denominator = Self::from_i128(BPS_DENOMINATOR).ok_or(FixedPointError::Overflow)?;
+        if fee_bps < Self::ZERO || fee_bps >= denominator {
+            return Err(FixedPointError::InvalidInput);
+        }
+        self.try_fixed_div(denominator - fee_bps, denominator, rounding)
+    }
+
+    /// Safely calculates the gross amount that leaves x after a fee of `fee_bps` is
+    /// taken from it, as x * 10,000 / (10,000 - fee_bps), rounded in the direction of
+    /// `rounding`. Returns None if the fee is not between 0 and 9,999 bps, or if the
+    /// result overflows.
+    fn gross_up_for_fee_rounded(self, fee_bps: Self, rounding: Rounding) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_gross_up_for_fee_rounded(fee_bps, rounding).ok()
+    }
+
+    /// Safely calculates the gross amount that leaves x after a fee of `fee_bps` is
+    /// taken from it, as ceil(x * 10,000 / (10,000 - fee_bps)), such that the protocol
+    /// never receives less than x after the fee. Returns an error if the fee is not
+    /// between 0 and 9,999 bps, or if the result does not fit in the type.
+    fn try_gross_up_for_fee(self, fee_bps: Self) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        self.try_gross_up_for_fee_rounded(fee_bps, Rounding::Ceil)
+    }
+
+    /// Safely calculates the gross amount that leaves x after a fee of `fee_bps` is
+    /// taken from it, as ceil(x * 10,000 / (10,000 - fee_bps)), such that the protocol
+    /// never receives less than x after the fee. Returns None if the fee is not between
+    /// 0 and 9,999 bps, or if the result overflows.
+    fn gross_up_for_fee(self, fee_bps: Self) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_gross_up_for_fee(fee_bps).ok()
+    }
+
+    /// Safely calculates x^exp, where x is a fixed-point number with `scale` as 1 and
+    /// exp is a whole number, using exponentiation by squaring. Returns an error if
+    /// an intermediate value or the result does not fit in the type, or if the
+    /// scale is 0.
+    ///
+    /// Every multiplication is rounded in the direction of `rounding`, so for a
+    /// non-negative x the result is bounded by the exact value on that side.
+    fn try_fixed_pow(
+        self,
+        exp: u32,
+        scale: Self,
+        rounding: Rounding,
+    ) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        if scale == Self::ZERO {
+            return Err(FixedPointError::DivideByZero);
+        }
+        let negative = self < Self::ZERO && exp % 2 == 1;
+        let mut result = scale;
+        let mut base = self;
+        let mut exp = exp;
+        while exp > 0 {
+            if exp % 2 == 1 {
+                result = result.try_fixed_mul(base, scale, rounding)?;
+            }
+            exp /= 2;
+            if exp > 0 {
+                // |base| > 1 if squaring overflows, so the result would overflow as well
+                base = base
+                    .try_fixed_mul(base, scale, rounding)
+                    .map_err(|err| match err {
+                        FixedPointError::Overflow if negative => FixedPointError::Underflow,
+                        err => err,
+                    })?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Safely calculates x^exp, where x is a fixed-point number with `scale` as 1 and
+    /// exp is a whole number, using exponentiation by squaring. Returns None if an
+    /// intermediate value or the result overflows, or if the scale is 0.
+    ///
+    /// Every multiplication is rounded in the direction of `rounding`, so for a
+    /// non-negative x the result is bounded by the exact value on that side.
+    fn fixed_pow(self, exp: u32, scale: Self, rounding: Rounding) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_fixed_pow(exp, scale, rounding).ok()
+    }
+
+    /// Safely calculates x^exp, where both x and exp are fixed-point numbers with
+    /// `scale` as 1, as e^(exp * ln(x)). Returns an error if x is negative, if the
+    /// result does not fit in the type, or if x or exp can't be represented as an
+    /// i128.
+    ///
+    /// The result is rounded to the nearest value. The intermediate values are
+    /// computed with 36 decimals, and the relative error before the final rounding is
+    /// below `(1 + |exp / scale|) * 1e-33`. For example, the result is within 1 of the
+    /// exact value for any result below 10^30 and exponents up to 100.
+    fn try_fixed_pow_frac(self, exp: Self, scale: Self) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        let x = self.to_i128().ok_or(FixedPointError::Overflow)?;
+        let exp = exp.to_i128().ok_or(FixedPointError::Overflow)?;
+        let scale = scale.to_i128().ok_or(FixedPointError::Overflow)?;
+        from_i128_result(exp_log::pow(x, exp, scale)?)
+    }
+
+    /// Safely calculates x^exp, where both x and exp are fixed-point numbers with
+    /// `scale` as 1, as e^(exp * ln(x)). Returns None if x is negative, if the result
+    /// overflows, or if x or exp can't be represented as an i128.
+    ///
+    /// The result is rounded to the nearest value. See `try_fixed_pow_frac` for
+    /// the error bounds.
+    fn fixed_pow_frac(self, exp: Self, scale: Self) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_fixed_pow_frac(exp, scale).ok()
+    }
+
+    /// Safely converts x from `from_decimals` to `to_decimals` decimal places. Digits
+    /// dropped when scaling down are rounded in the direction of `rounding`. Returns
+    /// an error if the result does not fit in the type.
+    fn try_rescale(
+        self,
+        from_decimals: u32,
+        to_decimals: u32,
+        rounding: Rounding,
+    ) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        rescale::rescale(self, from_decimals, to_decimals, rounding)
+    }
+
+    /// Safely converts x from `from_decimals` to `to_decimals` decimal places. Digits
+    /// dropped when scaling down are rounded in the direction of `rounding`. Returns
+    /// None if the result overflows.
+    fn rescale(self, from_decimals: u32, to_decimals: u32, rounding: Rounding) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_rescale(from_decimals, to_decimals, rounding).ok()
+    }
+
+    /// Safely converts x from `STROOP` scale to `to_decimals` decimal places. Returns
+    /// None if the result overflows.
+    fn rescale_from_stroop(self, to_decimals: u32, rounding: Rounding) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.rescale(STROOP_DECIMALS, to_decimals, rounding)
+    }
+
+    /// Safely converts x from `from_decimals` decimal places to `STROOP` scale. Returns
+    /// None if the result overflows.
+    fn rescale_to_stroop(self, from_decimals: u32, rounding: Rounding) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.rescale(from_decimals, STROOP_DECIMALS, rounding)
+    }
+
+    /// Safely calculates ln(x), where x is a fixed-point number with `scale` as 1.
+    /// Returns an error if x is not positive, if the result does not fit in the type,
+    /// or if x can't be represented as an i128.
+    ///
+    /// The result is rounded to the nearest value. The intermediate value is computed
+    /// with 36 decimals and is within 1e-33 of the exact value.
+    fn try_fixed_ln(self, scale: Self) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        let x = self.to_i128().ok_or(FixedPointError::Overflow)?;
+        let scale = scale.to_i128().ok_or(FixedPointError::Overflow)?;
+        from_i128_result(exp_log::fixed_ln(x, scale)?)
+    }
+
+    /// Safely calculates ln(x), where x is a fixed-point number with `scale` as 1.
+    /// Returns None if x is not positive, if the result overflows, or if x can't be
+    /// represented as an i128.
+    ///
+    /// The result is rounded to the nearest value.
+    fn fixed_ln(self, scale: Self) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_fixed_ln(scale).ok()
+    }
+
+    /// Safely calculates log2(x), where x is a fixed-point number with `scale` as 1.
+    /// Returns an error if x is not positive, if the result does not fit in the type,
+    /// or if x can't be represented as an i128.
+    ///
+    /// The result is rounded to the nearest value, and is exact for powers of two.
+    fn try_fixed_log2(self, scale: Self) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        let x = self.to_i128().ok_or(FixedPointError::Overflow)?;
+        let scale = scale.to_i128().ok_or(FixedPointError::Overflow)?;
+        from_i128_result(exp_log::fixed_log2(x, scale)?)
+    }
+
+    /// Safely calculates log2(x), where x is a fixed-point number with `scale` as 1.
+    /// Returns None if x is not positive, if the result overflows, or if x can't be
+    /// represented as an i128.
+    ///
+    /// The result is rounded to the nearest value, and is exact for powers of two.
+    fn fixed_log2(self, scale: Self) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_fixed_log2(scale).ok()
+    }
+
+    /// Safely calculates e^x, where x is a fixed-point number with `scale` as 1.
+    /// Returns an error if the result does not fit in the type, or if x can't be
+    /// represented as an i128.
+    ///
+    /// The result is rounded to the nearest value, and the relative error before the
+    /// final rounding is below `(1 + |x / scale|) * 1e-33`.
+    fn try_fixed_exp(self, scale: Self) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        let x = self.to_i128().ok_or(FixedPointError::Overflow)?;
+        let scale = scale.to_i128().ok_or(FixedPointError::Overflow)?;
+        from_i128_result(exp_log::fixed_exp(x, scale)?)
+    }
+
+    /// Safely calculates e^x, where x is a fixed-point number with `scale` as 1.
+    /// Returns None if the result overflows, or if x can't be represented as an i128.
+    ///
+    /// The result is rounded to the nearest value.
+    fn fixed_exp(self, scale: Self) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_fixed_exp(scale).ok()
+    }
+
+    /// Safely calculates sqrt(x), where x is a fixed-point number with `scale` as 1,
+    /// rounded in the direction of `rounding`. Returns an error if x is negative or
+    /// if the scale is not positive.
+    ///
+    /// The root is computed as sqrt(x * scale) with Newton's method, and the product
+    /// is held in 256 bits so it cannot overflow.
+    fn try_fixed_sqrt(self, scale: Self, rounding: Rounding) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        if scale == Self::ZERO {
+            return Err(FixedPointError::DivideByZero);
+        }
+        if self < Self::ZERO || scale < Self::ZERO {
+            return Err(FixedPointError::InvalidInput);
+        }
+        let x = self.to_u128().ok_or(FixedPointError::Overflow)?;
+        let scale = scale.to_u128().ok_or(FixedPointError::Overflow)?;
+        let product = U256::mul_u128(x, scale);
+        let root = product.sqrt();
+
+        // the exact root is past the midpoint of root and root + 1 if
+        // product > (root + 0.5)^2, or product > root^2 + root for integers
+        let square = U256::mul_u128(root, root);
+        let remainder = if product == square {
+            Remainder::Zero
+        } else if product
+            <= square
+                .checked_add_u128(root)
+                .ok_or(FixedPointError::Overflow)?
+        {
+            Remainder::BelowHalf
+        } else {
+            Remainder::AboveHalf
+        };
+        let root = if rounding.round_away_from_zero(false, root % 2 == 1, remainder) {
+            root.checked_add(1).ok_or(FixedPointError::Overflow)?
+        } else {
+            root
+        };
+        Self::from_u128(root).ok_or(FixedPointError::Overflow)
+    }
+
+    /// Safely calculates floor(sqrt(x)), where x is a fixed-point number with `scale`
+    /// as 1. Returns an error if x is negative or if the scale is not positive.
+    fn try_fixed_sqrt_floor(self, scale: Self) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        self.try_fixed_sqrt(scale, Rounding::Floor)
+    }
+
+    /// Safely calculates ceil(sqrt(x)), where x is a fixed-point number with `scale`
+    /// as 1. Returns an error if x is negative or if the scale is not positive.
+    fn try_fixed_sqrt_ceil(self, scale: Self) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        self.try_fixed_sqrt(scale, Rounding::Ceil)
+    }
+
+    /// Safely calculates sqrt(x), where x is a fixed-point number with `scale` as 1,
+    /// rounded in the direction of `rounding`. Returns None if x is negative or if
+    /// the scale is not positive.
+    fn fixed_sqrt(self, scale: Self, rounding: Rounding) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_fixed_sqrt(scale, rounding).ok()
+    }
+
+    /// Safely calculates floor(sqrt(x)), where x is a fixed-point number with `scale`
+    /// as 1. Returns None if x is negative or if the scale is not positive.
+    fn fixed_sqrt_floor(self, scale: Self) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_fixed_sqrt_floor(scale).ok()
+    }
+
+    /// Safely calculates ceil(sqrt(x)), where x is a fixed-point number with `scale`
+    /// as 1. Returns None if x is negative or if the scale is not positive.
+    fn fixed_sqrt_ceil(self, scale: Self) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        self.try_fixed_sqrt_ceil(scale).ok()
+    }
+
+    /// Returns an adapter that displays x as a decimal number with `decimals` decimal
+    /// places, such as `1.5000000` for `1_5000000` with 7 decimals
+    fn display(self, decimals: u32) -> FixedDisplay<Self>
+    where
+        Self: Integer,
+    {
+        FixedDisplay::new(self, decimals)
+    }
+
+    /// Parses a decimal string, such as `-1.5`, into a raw value with `decimals`
+    /// decimal places. Returns an error if the string is not a decimal number, if it
+    /// has nonzero fractional digits beyond `decimals`, or if the value does not fit in
+    /// the type.
+    fn try_parse_fixed(s: &str, decimals: u32) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        format::parse(s, decimals, None)
+    }
+
+    /// Parses a decimal string, such as `-1.5`, into a raw value with `decimals`
+    /// decimal places. Returns None if the string is not a decimal number, if it has
+    /// nonzero fractional digits beyond `decimals`, or if the value overflows.
+    fn parse_fixed(s: &str, decimals: u32) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        Self::try_parse_fixed(s, decimals).ok()
+    }
+
+    /// Parses a decimal string, such as `-1.5`, into a raw value with `decimals`
+    /// decimal places, rounding any extra fractional digits in the direction of
+    /// `rounding`. Returns an error if the string is not a decimal number or if the
+    /// value does not fit in the type.
+    fn try_parse_fixed_rounded(
+        s: &str,
+        decimals: u32,
+        rounding: Rounding,
+    ) -> Result<Self, FixedPointError>
+    where
+        Self: Integer,
+    {
+        format::parse(s, decimals, Some(rounding))
+    }
+
+    /// Parses a decimal string, such as `-1.5`, into a raw value with `decimals`
+    /// decimal places, rounding any extra fractional digits in the direction of
+    /// `rounding`. Returns None if the string is not a decimal number or if the value
+    /// overflows.
+    fn parse_fixed_rounded(s: &str, decimals: u32, rounding: Rounding) -> Option<Self>
+    where
+        Self: Integer,
+    {
+        Self::try_parse_fixed_rounded(s, decimals, rounding).ok()
+    }
+}
+
+/// Converts an i128 result back to the integer type, returning an error that matches
+/// the sign of the result if it does not fit
+fn from_i128_result<T: Integer>(result: i128) -> Result<T, FixedPointError> {
+    T::from_i128(result).ok_or(if result < 0 {
+        FixedPointError::Underflow
+    } else {
+        FixedPointError::Overflow
+    })
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use super::*;
+
+    /********** saturating **********/
+
+    #[test]
+    fn test_saturating_matches_checked() {
+        let x: i128 = 1_5391283;
+        let y: i128 = 314_1592653;
+        let denominator: i128 = 1_0000000;
+
+        assert_eq!(x.saturating_fixed_mul_floor(y, denominator), 483_5314159);
+        assert_eq!(x.saturating_fixed_mul_ceil(y, denominator), 483_5314160);
+        assert_eq!(
+            y.saturating_fixed_div_floor(1_5391280, denominator),
+            204_1150997
+        );
+        assert_eq!(
+            y.saturating_fixed_div_ceil(1_5391280, denominator),
+            204_1150998
+        );
+        assert_eq!(
+            (-x).saturating_fixed_mul_floor(y, denominator),
+            -483_5314160
+        );
+    }
+
+    #[test]
+    fn test_saturating_clamps() {
+        let x: i128 = i128::MAX / 2;
+        let denominator: i128 = 1_0000000;
+
+        assert_eq!(
+            x.saturating_fixed_mul_floor(3_0000000, denominator),
+            i128::MAX
+        );
+        assert_eq!(
+            x.saturating_fixed_mul_ceil(-3_0000000, denominator),
+            i128::MIN
+        );
+        assert_eq!(
+            x.saturating_fixed_div_floor(2500000, denominator),
+            i128::MAX
+        );
+        assert_eq!(u64::MAX.saturating_fixed_mul_ceil(2, 1), u64::MAX);
+        assert_eq!(i32::MIN.saturating_fixed_div_floor(-1, 1), i32::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to divide by zero")]
+    fn test_saturating_mul_divide_by_zero() {
+        5_i64.saturating_fixed_mul_floor(3, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to divide by zero")]
+    fn test_saturating_div_divide_by_zero() {
+        5_u32.saturating_fixed_div_ceil(0, 1_0000000);
+    }
+
+    /// An integer whose fixed-point multiplication always fails with `PhantomOverflow`
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Phantom(i64);
+
+    macro_rules! impl_phantom_op {
+        ($($op:ident::$method:ident),*) => {
+            $(
+                impl core::ops::$op for Phantom {
+                    type Output = Phantom;
+
+                    fn $method(self, rhs: Phantom) -> Phantom {
+                        Phantom(core::ops::$op::$method(self.0, rhs.0))
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_phantom_op!(Add::add, Sub::sub, Mul::mul, Div::div, Rem::rem);
+
+    impl Integer for Phantom {
+        const ZERO: Phantom = Phantom(0);
+        const ONE: Phantom = Phantom(1);
+        const TEN: Phantom = Phantom(10);
+        const MIN: Phantom = Phantom(i64::MIN);
+        const MAX: Phantom = Phantom(i64::MAX);
+
+        fn checked_add(self, rhs: Phantom) -> Option<Phantom> {
+            self.0.checked_add(rhs.0).map(Phantom)
+        }
+
+        fn checked_sub(self, rhs: Phantom) -> Option<Phantom> {
+            self.0.checked_sub(rhs.0).map(Phantom)
+        }
+
+        fn checked_mul(self, rhs: Phantom) -> Option<Phantom> {
+            self.0.checked_mul(rhs.0).map(Phantom)
+        }
+
+        fn checked_pow(self, exp: u32) -> Option<Phantom> {
+            self.0.checked_pow(exp).map(Phantom)
+        }
+
+        fn wrapping_add(self, rhs: Phantom) -> Phantom {
+            Phantom(self.0.wrapping_add(rhs.0))
+        }
+
+        fn wrapping_sub(self, rhs: Phantom) -> Phantom {
+            Phantom(self.0.wrapping_sub(rhs.0))
+        }
+
+        fn wrapping_mul(self, rhs: Phantom) -> Phantom {
+            Phantom(self.0.wrapping_mul(rhs.0))
+        }
+
+        fn to_i128(self) -> Option<i128> {
+            Some(i128::from(self.0))
+        }
+
+        fn from_i128(value: i128) -> Option<Phantom> {
+            i64::try_from(value).ok().map(Phantom)
+        }
+
+        fn to_u128(self) -> Option<u128> {
+            u128::try_from(self.0).ok()
+        }
+
+        fn from_u128(value: u128) -> Option<Phantom> {
+            i64::try_from(value).ok().map(Phantom)
+        }
+    }
+
+    impl FixedPoint for Phantom {
+        fn try_fixed_mul(
+            self,
+            _y: Phantom,
+            _denominator: Phantom,
+            _rounding: Rounding,
+        ) -> Result<Phantom, FixedPointError> {
+            Err(FixedPointError::PhantomOverflow)
+        }
+    }
+
+    #[test]
+    fn test_saturating_phantom_overflow() {
+        let x = Phantom(5);
+
+        assert_eq!(
+            x.saturating_fixed_mul_floor(Phantom(3), Phantom(1)),
+            Phantom::MAX
+        );
+        assert_eq!(
+            x.saturating_fixed_mul_floor(Phantom(-3), Phantom(1)),
+            Phantom::MIN
+        );
+        assert_eq!(
+            x.saturating_fixed_mul_ceil(Phantom(3), Phantom(-1)),
+            Phantom::MIN
+        );
+        assert_eq!(
+            x.saturating_fixed_div_ceil(Phantom(-3), Phantom(-1)),
+            Phantom::MAX
+        );
+    }
+
+    /********** mul_mul_div **********/
+
+    #[test]
+    fn test_mul_mul_div_rounds_once() {
+        let amount: i128 = 1000_0000004;
+        let price: i128 = 1_2345678;
+        let rate: i128 = 3333333;
+
+        // chaining fixed_mul_floor rounds twice, and loses a stroop
+        let chained = amount
+            .fixed_mul_floor(price, 1_0000000)
+            .and_then(|value| value.fixed_mul_floor(rate, 1_0000000))
+            .unwrap();
+        let result = amount
+            .mul_mul_div_floor(price, rate, 1_0000000, 1_0000000)
+            .unwrap();
+
+        assert_eq!(chained, 411_5225589);
+        assert_eq!(result, 411_5225590);
+        assert_eq!(
+            amount.mul_mul_div_ceil(price, rate, 1_0000000, 1_0000000),
+            Some(411_5225591)
+        );
+    }
+
+    #[test]
+    fn test_mul_mul_div_u64() {
+        let x = u64::MAX / 3;
+
+        // (u64::MAX / 3)^2 * 10 / u64::MAX^2 = 10 / 9
+        assert_eq!(x.mul_mul_div_floor(x, 10, u64::MAX, u64::MAX), Some(1));
+        assert_eq!(x.mul_mul_div_ceil(x, 10, u64::MAX, u64::MAX), Some(2));
+        assert_eq!(
+            x.try_mul_mul_div_floor(x, 9, u64::MAX, 0),
+            Err(FixedPointError::DivideByZero)
+        );
+    }
+
+    /********** bps and percent **********/
+
+    #[test]
+    fn test_apply_bps() {
+        let amount: i128 = 1234_5678901;
+
+        assert_eq!(amount.apply_bps(30), Some(3_7037037));
+        assert_eq!(
+            amount.apply_bps_rounded(30, Rounding::Floor),
+            Some(3_7037036)
+        );
+        assert_eq!(amount.apply_bps(0), Some(0));
+        assert_eq!(amount.apply_bps(10_000), Some(amount));
+        assert_eq!(1_u64.apply_bps(1), Some(1));
+        assert_eq!(1_u64.apply_bps_rounded(1, Rounding::Floor), Some(0));
+        assert_eq!((-1_i128).apply_bps(1), Some(0));
+    }
+
+    #[test]
+    fn test_apply_bps_bounds() {
+        // the intermediate product is held in a wider type, so only the result can overflow
+        assert_eq!(
+            i128::MAX.apply_bps_rounded(5_000, Rounding::Floor),
+            Some(i128::MAX / 2)
+        );
+        assert_eq!(u64::MAX.apply_bps(10_000), Some(u64::MAX));
+        assert_eq!(
+            u64::MAX.try_apply_bps(10_001),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!(
+            i128::MIN.try_apply_bps(20_000),
+            Err(FixedPointError::Underflow)
+        );
+    }
+
+    #[test]
+    fn test_apply_percent() {
+        let amount: u64 = 1_0000001;
+
+        assert_eq!(amount.apply_percent(15), Some(1500001));
+        assert_eq!(
+            amount.apply_percent_rounded(15, Rounding::Floor),
+            Some(1500000)
+        );
+        assert_eq!(i128::MAX.apply_percent(100), Some(i128::MAX));
+        assert_eq!(
+            i128::MAX.try_apply_percent(101),
+            Err(FixedPointError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_gross_up_for_fee() {
+        let net: i128 = 100_0000000;
+
+        let gross = net.gross_up_for_fee(30).unwrap();
+
+        // 100 / 0.997 = 100.30090270812437...
+        assert_eq!(gross, 100_3009028);
+        assert_eq!(
+            net.gross_up_for_fee_rounded(30, Rounding::Floor),
+            Some(100_3009027)
+        );
+        // taking the fee from the gross amount leaves at least the net amount
+        assert!(gross - gross.apply_bps(30).unwrap() >= net);
+        assert_eq!(net.gross_up_for_fee(0), Some(net));
+        assert_eq!(1_u64.gross_up_for_fee(9_999), Some(10_000));
+    }
+
+    #[test]
+    fn test_gross_up_for_fee_errors() {
+        assert_eq!(
+            1_u64.try_gross_up_for_fee(10_000),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            1_i128.try_gross_up_for_fee(-1),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            u64::MAX.try_gross_up_for_fee(1),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!(i128::MAX.gross_up_for_fee(9_999), None);
+    }
+
+    /********** fixed_pow **********/
+
+    #[test]
+    fn test_fixed_pow_rounds() {
+        let x: i128 = 1_0500000;
+        let scale: i128 = 1_0000000;
+
+        let floor = x.fixed_pow(10, scale, Rounding::Floor).unwrap();
+        let ceil = x.fixed_pow(10, scale, Rounding::Ceil).unwrap();
+
+        // 1.05^10 = 1.62889462677744140625
+        assert_eq!(floor, 1_6288944);
+        assert_eq!(ceil, 1_6288948);
+    }
+
+    #[test]
+    fn test_fixed_pow_compounding() {
+        let x: u64 = 1_0001000;
+        let scale: u64 = 1_0000000;
+
+        let floor = x.fixed_pow(365, scale, Rounding::Floor).unwrap();
+        let ceil = x.fixed_pow(365, scale, Rounding::Ceil).unwrap();
+
+        // 1.0001^365 = 1.03717241130255192990...
+        assert_eq!(floor, 1_0371615);
+        assert_eq!(ceil, 1_0371990);
+    }
+
+    #[test]
+    fn test_fixed_pow_zero_exp() {
+        let x: i128 = 123_4567890;
+        let scale: i128 = 1_0000000;
+
+        let result = x.fixed_pow(0, scale, Rounding::Floor).unwrap();
+
+        assert_eq!(result, scale);
+    }
+
+    #[test]
+    fn test_fixed_pow_negative() {
+        let x: i128 = -1_5000000;
+        let scale: i128 = 1_0000000;
+
+        let odd = x.fixed_pow(3, scale, Rounding::Floor).unwrap();
+        let even = x.fixed_pow(4, scale, Rounding::Floor).unwrap();
+
+        assert_eq!(odd, -3_3750000);
+        assert_eq!(even, 5_0625000);
+    }
+
+    #[test]
+    fn test_fixed_pow_overflow() {
+        let scale: i128 = 1_000_000_000_000_000_000;
+
+        let positive = (2 * scale).try_fixed_pow(128, scale, Rounding::Floor);
+        let negative = (-2 * scale).try_fixed_pow(129, scale, Rounding::Floor);
+
+        assert_eq!(positive, Err(FixedPointError::Overflow));
+        assert_eq!(negative, Err(FixedPointError::Underflow));
+    }
+
+    #[test]
+    fn test_fixed_pow_zero_scale() {
+        let result = 2_u64.try_fixed_pow(0, 0, Rounding::Floor);
+
+        assert_eq!(result, Err(FixedPointError::DivideByZero));
+    }
+
+    /********** fixed_pow_frac **********/
+
+    #[test]
+    fn test_fixed_pow_frac_sqrt() {
+        let x: i128 = 2_000_000_000_000_000_000;
+        let exp: i128 = 500_000_000_000_000_000;
+        let scale: i128 = 1_000_000_000_000_000_000;
+
+        let result = x.fixed_pow_frac(exp, scale).unwrap();
+
+        // 2^0.5 = 1.41421356237309504880...
+        assert_eq!(result, 1_414_213_562_373_095_049);
+    }
+
+    #[test]
+    fn test_fixed_pow_frac_exact() {
+        let x: i128 = 4_000_000_000_000_000_000;
+        let exp: i128 = 1_500_000_000_000_000_000;
+        let scale: i128 = 1_000_000_000_000_000_000;
+
+        let result = x.fixed_pow_frac(exp, scale).unwrap();
+
+        assert_eq!(result, 8_000_000_000_000_000_000);
+    }
+
+    #[test]
+    fn test_fixed_pow_frac_known_values() {
+        // 1.5^2.5 = 2.75567596063107536047...
+        assert_eq!(
+            1_500_000_000_000_000_000_i128
+                .fixed_pow_frac(2_500_000_000_000_000_000, 1_000_000_000_000_000_000),
+            Some(2_755_675_960_631_075_360)
+        );
+        // 1.05^0.5 = 1.02469507659595983832...
+        assert_eq!(
+            1_0500000_u64.fixed_pow_frac(5000000, 1_0000000),
+            Some(1_0246951)
+        );
+        // 0.5^-1.5 = 2.82842712474619009760...
+        assert_eq!(
+            5000000_i64.fixed_pow_frac(-1_5000000, 1_0000000),
+            Some(2_8284271)
+        );
+        // 10^-1.5 = 0.03162277660168379332...
+        assert_eq!(
+            10_000_000_000_u128.fixed_pow_frac(1_500_000_000, 1_000_000_000),
+            Some(31_622_776_602)
+        );
+    }
+
+    #[test]
+    fn test_fixed_pow_frac_zero_base() {
+        let scale: i128 = 1_0000000;
+
+        assert_eq!(0.fixed_pow_frac(5000000, scale), Some(0));
+        assert_eq!(0.fixed_pow_frac(0, scale), Some(scale));
+        assert_eq!(
+            0.try_fixed_pow_frac(-5000000, scale),
+            Err(FixedPointError::DivideByZero)
+        );
+    }
+
+    #[test]
+    fn test_fixed_pow_frac_errors() {
+        let scale: i128 = 1_0000000;
+
+        assert_eq!(
+            (-2 * scale).try_fixed_pow_frac(5000000, scale),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            (10 * scale).try_fixed_pow_frac(40 * scale, scale),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!(
+            u128::MAX.try_fixed_pow_frac(5000000, 1_0000000),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!((10 * scale).fixed_pow_frac(-40 * scale, scale), Some(0));
+    }
+
+    /********** fixed_sqrt **********/
+
+    #[test]
+    fn test_fixed_sqrt_rounds() {
+        let x: i128 = 2_000_000_000_000_000_000;
+        let scale: i128 = 1_000_000_000_000_000_000;
+
+        let floor = x.fixed_sqrt_floor(scale).unwrap();
+        let ceil = x.fixed_sqrt_ceil(scale).unwrap();
+
+        // sqrt(2) = 1.41421356237309504880...
+        assert_eq!(floor, 1_414_213_562_373_095_048);
+        assert_eq!(ceil, 1_414_213_562_373_095_049);
+    }
+
+    #[test]
+    fn test_fixed_sqrt_exact() {
+        let x: u64 = 6_2500000;
+        let scale: u64 = 1_0000000;
+
+        assert_eq!(x.fixed_sqrt_floor(scale), Some(2_5000000));
+        assert_eq!(x.fixed_sqrt_ceil(scale), Some(2_5000000));
+        assert_eq!(0_i32.fixed_sqrt_ceil(1000), Some(0));
+    }
+
+    #[test]
+    fn test_fixed_sqrt_nearest() {
+        // sqrt(0.000002) = 0.00141421356...
+        assert_eq!(20_i64.fixed_sqrt(1_0000000, Rounding::HalfUp), Some(14142));
+        // sqrt(0.0000003) = 0.000547722557...
+        assert_eq!(3_u32.fixed_sqrt(1_0000000, Rounding::HalfEven), Some(5477));
+        // sqrt(0.0000006) = 0.000774596669...
+        assert_eq!(6_u32.fixed_sqrt(1_0000000, Rounding::HalfEven), Some(7746));
+    }
+
+    #[test]
+    fn test_fixed_sqrt_phantom_overflow() {
+        let x: u64 = 1_844_674_407_370_955_161;
+        let scale: u64 = 1_0000000;
+
+        let result = x.fixed_sqrt_floor(scale).unwrap();
+
+        // sqrt(184467440737.0955161) = 429496.72959999999...
+        assert_eq!(result, 4_294_967_295_999);
+    }
+
+    #[test]
+    fn test_fixed_sqrt_max() {
+        assert_eq!(u128::MAX.fixed_sqrt_floor(u128::MAX), Some(u128::MAX));
+        assert_eq!(
+            i128::MAX.fixed_sqrt_ceil(1),
+            Some(13_043_817_825_332_782_213)
+        );
+        assert_eq!(u32::MAX.fixed_sqrt_ceil(1), Some(65_536));
+    }
+
+    #[test]
+    fn test_fixed_sqrt_errors() {
+        assert_eq!(
+            (-1_i128).try_fixed_sqrt_floor(1_0000000),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            1_i64.try_fixed_sqrt_ceil(-1_0000000),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            1_u64.try_fixed_sqrt_floor(0),
+            Err(FixedPointError::DivideByZero)
+        );
+        assert_eq!((-4_i32).fixed_sqrt_ceil(1), None);
+    }
+
+    /********** fixed_ln, fixed_log2 and fixed_exp **********/
+
+    #[test]
+    fn test_fixed_ln_stroop() {
+        let scale: i128 = 1_0000000;
+
+        assert_eq!(2_0000000.fixed_ln(scale), Some(6931472));
+        assert_eq!(5000000.fixed_ln(scale), Some(-6931472));
+        assert_eq!(123_4560000.fixed_ln(scale), Some(4_8158848));
+        assert_eq!(1.fixed_ln(scale), Some(-16_1180957));
+        assert_eq!(scale.fixed_ln(scale), Some(0));
+    }
+
+    #[test]
+    fn test_fixed_ln_decimals() {
+        let scale_9: i128 = 1_000_000_000;
+        let scale_18: i128 = 1_000_000_000_000_000_000;
+
+        assert_eq!(123_456_000_000.fixed_ln(scale_9), Some(4_815_884_817));
+        assert_eq!(100.fixed_ln(scale_9), Some(-16_118_095_651));
+        assert_eq!(
+            123_456_000_000_000_000_000.fixed_ln(scale_18),
+            Some(4_815_884_817_283_263_883)
+        );
+        assert_eq!(
+            100_000_000_000.fixed_ln(scale_18),
+            Some(-16_118_095_650_958_319_788)
+        );
+    }
+
+    #[test]
+    fn test_fixed_log2() {
+        assert_eq!(10_0000000_i128.fixed_log2(1_0000000), Some(3_3219281));
+        assert_eq!(
+            300_000_000_i128.fixed_log2(1_000_000_000),
+            Some(-1_736_965_594)
+        );
+        assert_eq!(
+            1_000_000_000_000_000_000_000_000_i128.fixed_log2(1_000_000_000_000_000_000),
+            Some(19_931_568_569_324_174_087)
+        );
+        assert_eq!(1024_0000000_i128.fixed_log2(1_0000000), Some(10_0000000));
+    }
+
+    #[test]
+    fn test_fixed_exp() {
+        let scale_7: i128 = 1_0000000;
+        let scale_9: i128 = 1_000_000_000;
+        let scale_18: i128 = 1_000_000_000_000_000_000;
+
+        assert_eq!(scale_7.fixed_exp(scale_7), Some(2_7182818));
+        assert_eq!((-2_5000000).fixed_exp(scale_7), Some(820850));
+        assert_eq!(
+            (20 * scale_7).fixed_exp(scale_7),
+            Some(4_851_651_954_097_903)
+        );
+        assert_eq!((-2_500_000_000).fixed_exp(scale_9), Some(82_084_999));
+        assert_eq!(100_000.fixed_exp(scale_9), Some(1_000_100_005));
+        assert_eq!(
+            scale_18.fixed_exp(scale_18),
+            Some(2_718_281_828_459_045_235)
+        );
+        assert_eq!(
+            (20 * scale_18).fixed_exp(scale_18),
+            Some(485_165_195_409_790_277_969_106_831)
+        );
+        assert_eq!(
+            100_000_000_000_000.fixed_exp(scale_18),
+            Some(1_000_100_005_000_166_671)
+        );
+        assert_eq!(0.fixed_exp(scale_18), Some(scale_18));
+    }
+
+    #[test]
+    fn test_fixed_exp_large_exponents() {
+        let scale: i128 = 1_000_000_000_000_000_000;
+
+        assert_eq!(
+            (50 * scale).try_fixed_exp(scale),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!(
+            (10_000 * scale).try_fixed_exp(scale),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!((-50 * scale).fixed_exp(scale), Some(0));
+        assert_eq!(i128::MIN.fixed_exp(scale), Some(0));
+    }
+
+    #[test]
+    fn test_fixed_ln_errors() {
+        let scale: i128 = 1_0000000;
+
+        assert_eq!(0.try_fixed_ln(scale), Err(FixedPointError::InvalidInput));
+        assert_eq!(
+            (-scale).try_fixed_log2(scale),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(scale.try_fixed_ln(0), Err(FixedPointError::DivideByZero));
+        assert_eq!(
+            5000000_u64.try_fixed_ln(1_0000000),
+            Err(FixedPointError::Underflow)
+        );
+        assert_eq!(
+            u128::MAX.try_fixed_ln(1_0000000),
+            Err(FixedPointError::Overflow)
+        );
+    }
+
+    /********** rescale **********/
+
+    #[test]
+    fn test_rescale() {
+        let x: i128 = 123_456_789_012_345_678_901;
+
+        assert_eq!(x.rescale(18, 7, Rounding::Floor), Some(123_4567890));
+        assert_eq!(x.rescale(18, 7, Rounding::Ceil), Some(123_4567891));
+        assert_eq!(
+            123_4567890_i128.rescale(7, 18, Rounding::Floor),
+            Some(123_456_789_000_000_000_000)
+        );
+        assert_eq!(
+            1_u32.try_rescale(0, 10, Rounding::Floor),
+            Err(FixedPointError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_rescale_stroop() {
+        let x: u64 = 1_5000001;
+
+        assert_eq!(
+            x.rescale_from_stroop(9, Rounding::Floor),
+            Some(1_500_000_100)
+        );
+        assert_eq!(x.rescale_from_stroop(6, Rounding::Floor), Some(1_500_000));
+        assert_eq!(x.rescale_from_stroop(6, Rounding::Ceil), Some(1_500_001));
+        assert_eq!(
+            1_500_000_049_u64.rescale_to_stroop(9, Rounding::HalfUp),
+            Some(1_5000000)
+        );
+        assert_eq!(u64::MAX.rescale_from_stroop(8, Rounding::Floor), None);
+    }
+
+    /********** display and parse **********/
 
-    /// Safely calculates ceil(x * y / denominator). Returns None if a phantom overflow
-    /// occurs or if the denominator is 0.
-    fn fixed_mul_ceil(self, y: Self, denominator: Self) -> Option<Self>;
+    #[test]
+    fn test_display_parse() {
+        extern crate std;
+        use std::string::ToString;
 
-    /// Safely calculates floor(x * denominator / y). Returns None if a phantom overflow
-    /// occurs or if the denominator is 0.
-    fn fixed_div_floor(self, y: Self, denominator: Self) -> Option<Self>;
+        let x: i128 = -1_5000000;
 
-    /// Safely calculates ceil(x * denominator / y). Returns None if a phantom overflow
-    /// occurs or if the denominator is 0.
-    fn fixed_div_ceil(self, y: Self, denominator: Self) -> Option<Self>;
+        assert_eq!(x.display(7).to_string(), "-1.5000000");
+        assert_eq!(i128::parse_fixed("-1.5", 7), Some(x));
+        assert_eq!(u64::parse_fixed("1.23456789", 7), None);
+        assert_eq!(
+            u64::parse_fixed_rounded("1.23456789", 7, Rounding::Floor),
+            Some(1_2345678)
+        );
+        assert_eq!(
+            u32::try_parse_fixed("abc", 7),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            i32::try_parse_fixed_rounded("1000", 7, Rounding::Floor),
+            Err(FixedPointError::Overflow)
+        );
+    }
 }
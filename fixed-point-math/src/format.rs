@@ -0,0 +1,382 @@
+use core::fmt;
+
+use crate::{
+    error::FixedPointError,
+    integer::{from_magnitude, magnitude, Integer},
+    rounding::{Remainder, Rounding},
+};
+
+/// Formats a raw fixed-point value as a decimal string with `decimals` decimal places,
+/// such that `1_5000000` with 7 decimals is displayed as `1.5000000`
+///
+/// The width, fill, alignment and sign flags of the formatter are applied like they are
+/// for integers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FixedDisplay<T> {
+    value: T,
+    decimals: u32,
+}
+
+impl<T: Integer> FixedDisplay<T> {
+    pub fn new(value: T, decimals: u32) -> Self {
+        FixedDisplay { value, decimals }
+    }
+}
+
+/// The number of digits in `u128::MAX`
+const MAX_DIGITS: usize = 39;
+
+impl<T: Integer> fmt::Display for FixedDisplay<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rest = magnitude(self.value).ok_or(fmt::Error)?;
+        let mut buffer = [0_u8; MAX_DIGITS];
+        let mut start = MAX_DIGITS;
+        loop {
+            start -= 1;
+            buffer[start] = b'0' + (rest % 10) as u8;
+            rest /= 10;
+            if rest == 0 {
+                break;
+            }
+        }
+        let digits = core::str::from_utf8(&buffer[start..]).map_err(|_| fmt::Error)?;
+
+        // the fractional part can be longer than the magnitude, in which case it starts
+        // with zeros that are written without being buffered
+        let decimals = usize::try_from(self.decimals).map_err(|_| fmt::Error)?;
+        let (whole, zeros, fraction) = match digits.len().checked_sub(decimals) {
+            Some(0) => ("0", 0, digits),
+            Some(split) => (&digits[..split], 0, &digits[split..]),
+            None => ("0", decimals - digits.len(), digits),
+        };
+        let sign = if self.value < T::ZERO {
+            "-"
+        } else if f.sign_plus() {
+            "+"
+        } else {
+            ""
+        };
+        let length = sign.len() + whole.len() + if decimals > 0 { decimals + 1 } else { 0 };
+        let padding = f.width().unwrap_or(0).saturating_sub(length);
+
+        // pad like `Formatter::pad_integral`, which needs the digits in a single string
+        let (before, after) = match f.align() {
+            _ if f.sign_aware_zero_pad() => (0, 0),
+            Some(fmt::Alignment::Left) => (0, padding),
+            Some(fmt::Alignment::Center) => (padding / 2, padding.div_ceil(2)),
+            Some(fmt::Alignment::Right) | None => (padding, 0),
+        };
+        write_repeated(f, f.fill(), before)?;
+        f.write_str(sign)?;
+        if f.sign_aware_zero_pad() {
+            write_repeated(f, '0', padding)?;
+        }
+        f.write_str(whole)?;
+        if decimals > 0 {
+            f.write_str(".")?;
+            write_repeated(f, '0', zeros)?;
+            f.write_str(fraction)?;
+        }
+        write_repeated(f, f.fill(), after)
+    }
+}
+
+fn write_repeated(f: &mut fmt::Formatter<'_>, c: char, count: usize) -> fmt::Result {
+    for _ in 0..count {
+        fmt::Write::write_char(f, c)?;
+    }
+    Ok(())
+}
+
+/// Parses a decimal string into a raw fixed-point value with `decimals` decimal places.
+/// See `parse_magnitude` for the accepted strings.
+pub(crate) fn parse<T: Integer>(
+    s: &str,
+    decimals: u32,
+    rounding: Option<Rounding>,
+) -> Result<T, FixedPointError> {
+    let (negative, magnitude) = parse_magnitude(s, decimals, rounding)?;
+    from_magnitude(negative, magnitude).ok_or(if negative {
+        FixedPointError::Underflow
+    } else {
+        FixedPointError::Overflow
+    })
+}
+
+/// Parses a decimal string into the sign and magnitude of a raw fixed-point value with
+/// `decimals` decimal places. This is the only decimal parser, and it is usable in
+/// `const` contexts for the `fixed!` macro.
+///
+/// Accepts an optional sign followed by digits, optionally followed by a `.` and more
+/// digits. Underscores after a digit are ignored, like in Rust numeric literals.
+/// Fractional digits beyond `decimals` are rounded in the direction of `rounding`. If
+/// no rounding is given, they are rejected with `InvalidInput` unless they are all
+/// zeros.
+pub(crate) const fn parse_magnitude(
+    s: &str,
+    decimals: u32,
+    rounding: Option<Rounding>,
+) -> Result<(bool, u128), FixedPointError> {
+    let bytes = s.as_bytes();
+    let (negative, mut i) = match bytes.first() {
+        Some(b'-') => (true, 1),
+        Some(b'+') => (false, 1),
+        _ => (false, 0),
+    };
+    let mut magnitude: u128 = 0;
+    let mut overflow = false;
+    let mut fraction_digits: u32 = 0;
+    let mut in_fraction = false;
+    // if the current whole or fractional part has a digit yet
+    let mut has_digit = false;
+    // the first fractional digit beyond `decimals`, and if any digit after it is not 0
+    let mut excess: Option<u8> = None;
+    let mut excess_rest = false;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        if byte.is_ascii_digit() {
+            if in_fraction && fraction_digits == decimals {
+                match excess {
+                    Some(_) => excess_rest |= byte != b'0',
+                    None => excess = Some(byte),
+                }
+            } else {
+                match magnitude.checked_mul(10) {
+                    Some(value) => match value.checked_add((byte - b'0') as u128) {
+                        Some(value) => magnitude = value,
+                        None => overflow = true,
+                    },
+                    None => overflow = true,
+                }
+                if in_fraction {
+                    fraction_digits += 1;
+                }
+            }
+            has_digit = true;
+        } else if byte == b'.' && !in_fraction && has_digit {
+            in_fraction = true;
+            has_digit = false;
+        } else if byte != b'_' || !has_digit {
+            return Err(FixedPointError::InvalidInput);
+        }
+        i += 1;
+    }
+    if !has_digit {
+        return Err(FixedPointError::InvalidInput);
+    }
+    let out_of_bounds = if negative {
+        FixedPointError::Underflow
+    } else {
+        FixedPointError::Overflow
+    };
+    if overflow {
+        return Err(out_of_bounds);
+    }
+    if magnitude != 0 {
+        // pad the missing fractional digits with zeros
+        magnitude = match 10_u128.checked_pow(decimals - fraction_digits) {
+            Some(padding) => match magnitude.checked_mul(padding) {
+                Some(magnitude) => magnitude,
+                None => return Err(out_of_bounds),
+            },
+            None => return Err(out_of_bounds),
+        };
+    }
+
+    if let Some(first) = excess {
+        let remainder = if first == b'0' && !excess_rest {
+            Remainder::Zero
+        } else if first < b'5' {
+            Remainder::BelowHalf
+        } else if first == b'5' && !excess_rest {
+            Remainder::Half
+        } else {
+            Remainder::AboveHalf
+        };
+        let round_away = match (rounding, remainder) {
+            (_, Remainder::Zero) => false,
+            (Some(rounding), remainder) => {
+                rounding.round_away_from_zero(negative, magnitude % 2 == 1, remainder)
+            }
+            (None, _) => return Err(FixedPointError::InvalidInput),
+        };
+        if round_away {
+            magnitude = match magnitude.checked_add(1) {
+                Some(magnitude) => magnitude,
+                None => return Err(out_of_bounds),
+            };
+        }
+    }
+    Ok((negative, magnitude))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::{format, string::ToString};
+
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            FixedDisplay::new(1_5000000_i128, 7).to_string(),
+            "1.5000000"
+        );
+        assert_eq!(
+            FixedDisplay::new(-1_5000000_i64, 7).to_string(),
+            "-1.5000000"
+        );
+        assert_eq!(FixedDisplay::new(-5_i32, 7).to_string(), "-0.0000005");
+        assert_eq!(FixedDisplay::new(0_u32, 2).to_string(), "0.00");
+        assert_eq!(FixedDisplay::new(42_u64, 0).to_string(), "42");
+    }
+
+    #[test]
+    fn test_display_bounds() {
+        assert_eq!(
+            FixedDisplay::new(i128::MIN, 18).to_string(),
+            "-170141183460469231731.687303715884105728"
+        );
+        assert_eq!(
+            FixedDisplay::new(u128::MAX, 38).to_string(),
+            "3.40282366920938463463374607431768211455"
+        );
+        assert_eq!(
+            FixedDisplay::new(u128::MAX, 39).to_string(),
+            "0.340282366920938463463374607431768211455"
+        );
+        assert_eq!(FixedDisplay::new(12_u32, 10).to_string(), "0.0000000012");
+        assert_eq!(
+            format!("{}", FixedDisplay::new(i32::MIN, 9)),
+            "-2.147483648"
+        );
+    }
+
+    #[test]
+    fn test_display_pads() {
+        let x = FixedDisplay::new(-1_5000000_i128, 7);
+
+        assert_eq!(format!("{:>12}", x), "  -1.5000000");
+        assert_eq!(format!("{:<12}|", x), "-1.5000000  |");
+        assert_eq!(format!("{:*^14}", x), "**-1.5000000**");
+        assert_eq!(format!("{:012}", x), "-001.5000000");
+        assert_eq!(format!("{:+}", FixedDisplay::new(25_u32, 2)), "+0.25");
+    }
+
+    #[test]
+    fn test_display_more_decimals_than_digits() {
+        assert_eq!(FixedDisplay::new(12_u32, 11).to_string(), "0.00000000012");
+        assert_eq!(FixedDisplay::new(0_u32, 4).to_string(), "0.0000");
+        assert_eq!(
+            FixedDisplay::new(-1_i128, 45).to_string(),
+            "-0.000000000000000000000000000000000000000000001"
+        );
+        assert_eq!(
+            format!("{:>16}", FixedDisplay::new(-12_i32, 11)),
+            "  -0.00000000012"
+        );
+        assert_eq!(
+            format!("{:+016}", FixedDisplay::new(12_u32, 11)),
+            "+000.00000000012"
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse::<i128>("1.5", 7, None), Ok(1_5000000));
+        assert_eq!(parse::<i128>("-0.0000005", 7, None), Ok(-5));
+        assert_eq!(parse::<i128>("+12", 7, None), Ok(12_0000000));
+        assert_eq!(parse::<u64>("0.0", 7, None), Ok(0));
+        assert_eq!(parse::<u32>("-0", 7, None), Ok(0));
+        assert_eq!(parse::<u64>("000123.45", 2, None), Ok(12345));
+        assert_eq!(parse::<u64>("0", 50, None), Ok(0));
+        assert_eq!(parse::<i128>("-1_000.000_1", 7, None), Ok(-1000_0001000));
+    }
+
+    #[test]
+    fn test_parse_round_trips() {
+        for value in [i128::MIN, -1, 0, 1, 1_5000000, i128::MAX] {
+            let string = FixedDisplay::new(value, 7).to_string();
+
+            assert_eq!(parse::<i128>(&string, 7, None), Ok(value));
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for string in [
+            "", "-", "+", ".5", "1.", "1.2.3", "1,5", " 1", "1e7", "--1", "0x10", "_1", "1._5",
+        ] {
+            assert_eq!(
+                parse::<i128>(string, 7, None),
+                Err(FixedPointError::InvalidInput)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_excess_decimals() {
+        assert_eq!(
+            parse::<i128>("1.00000001", 7, None),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(parse::<i128>("1.00000000", 7, None), Ok(1_0000000));
+        assert_eq!(parse::<i128>("-0.5000000_000", 7, None), Ok(-5000000));
+        assert_eq!(parse::<u64>("12.000", 0, None), Ok(12));
+        assert_eq!(
+            parse::<i128>("1.00000001", 7, Some(Rounding::Floor)),
+            Ok(1_0000000)
+        );
+        assert_eq!(
+            parse::<i128>("1.00000001", 7, Some(Rounding::Ceil)),
+            Ok(1_0000001)
+        );
+        assert_eq!(
+            parse::<i128>("-1.00000001", 7, Some(Rounding::Floor)),
+            Ok(-1_0000001)
+        );
+        assert_eq!(
+            parse::<i128>("1.00000000", 7, Some(Rounding::Ceil)),
+            Ok(1_0000000)
+        );
+    }
+
+    #[test]
+    fn test_parse_excess_decimals_nearest() {
+        assert_eq!(parse::<u64>("2.5", 0, Some(Rounding::HalfEven)), Ok(2));
+        assert_eq!(parse::<u64>("3.5", 0, Some(Rounding::HalfEven)), Ok(4));
+        assert_eq!(parse::<u64>("2.5", 0, Some(Rounding::HalfUp)), Ok(3));
+        assert_eq!(parse::<u64>("2.50001", 0, Some(Rounding::HalfEven)), Ok(3));
+        assert_eq!(parse::<u64>("2.49999", 0, Some(Rounding::HalfUp)), Ok(2));
+        assert_eq!(parse::<i64>("-2.5", 0, Some(Rounding::HalfUp)), Ok(-3));
+        assert_eq!(parse::<i64>("0.05", 1, Some(Rounding::TowardZero)), Ok(0));
+    }
+
+    #[test]
+    fn test_parse_out_of_bounds() {
+        assert_eq!(
+            parse::<u32>("429.4967296", 7, None),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!(
+            parse::<u32>("-0.0000001", 7, None),
+            Err(FixedPointError::Underflow)
+        );
+        assert_eq!(parse::<i32>("-214.7483648", 7, None), Ok(i32::MIN));
+        assert_eq!(
+            parse::<i32>("214.7483647", 7, Some(Rounding::Ceil)),
+            Ok(i32::MAX)
+        );
+        assert_eq!(
+            parse::<i32>("214.74836471", 7, Some(Rounding::Ceil)),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!(parse::<u128>("1", 39, None), Err(FixedPointError::Overflow));
+        assert_eq!(
+            parse::<i128>("-999999999999999999999999999999999999999999", 0, None),
+            Err(FixedPointError::Underflow)
+        );
+    }
+}
@@ -0,0 +1,375 @@
+use core::cmp::Ordering;
+
+#[allow(deprecated)]
+use crate::{
+    error::FixedPointError,
+    fixed_point::FixedPoint,
+    integer::{from_magnitude, magnitude, Integer},
+    rounding::Rounding,
+    u256::U256,
+};
+
+/// An exact rational number `numerator / denominator`, such as the price of a Stellar
+/// DEX offer.
+///
+/// Fractions are always stored reduced by their greatest common divisor, with a
+/// positive denominator, so equal values have equal representations. Arithmetic is
+/// checked, and comparisons cross-multiply with 256-bit intermediates, so they never
+/// overflow.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Fraction<T> {
+    numerator: T,
+    denominator: T,
+}
+
+#[allow(deprecated)]
+impl<T: FixedPoint + Integer> Fraction<T> {
+    /// Safely creates the reduced fraction `numerator / denominator`. Returns an error
+    /// if the denominator is 0, or if the reduced fraction does not fit in `T`.
+    pub fn try_new(numerator: T, denominator: T) -> Result<Self, FixedPointError> {
+        if denominator == T::ZERO {
+            return Err(FixedPointError::DivideByZero);
+        }
+        let negative = (numerator < T::ZERO) != (denominator < T::ZERO);
+        let numerator = magnitude(numerator).ok_or(FixedPointError::Overflow)?;
+        let denominator = magnitude(denominator).ok_or(FixedPointError::Overflow)?;
+        let divisor = gcd(numerator, denominator);
+        Ok(Fraction {
+            numerator: from_magnitude(negative, numerator / divisor)
+                .ok_or(overflow_error(negative))?,
+            denominator: T::from_u128(denominator / divisor).ok_or(FixedPointError::Overflow)?,
+        })
+    }
+
+    /// Safely creates the reduced fraction `numerator / denominator`. Returns None if
+    /// the denominator is 0, or if the reduced fraction does not fit in `T`.
+    pub fn new(numerator: T, denominator: T) -> Option<Self> {
+        Self::try_new(numerator, denominator).ok()
+    }
+
+    /// Creates the fraction `value / 1`
+    pub fn from_integer(value: T) -> Self {
+        Fraction {
+            numerator: value,
+            denominator: T::ONE,
+        }
+    }
+
+    /// Returns the numerator of the reduced fraction
+    pub fn numerator(&self) -> T {
+        self.numerator
+    }
+
+    /// Returns the denominator of the reduced fraction, which is always positive
+    pub fn denominator(&self) -> T {
+        self.denominator
+    }
+
+    /// Safely calculates self + rhs. Returns an error if the result does not fit in `T`.
+    pub fn try_add(self, rhs: Self) -> Result<Self, FixedPointError> {
+        // a/b + c/d = (a * (d/g) + c * (b/g)) / (b * (d/g)), where g = gcd(b, d)
+        let divisor = self.denominator_gcd(&rhs)?;
+        let lhs_factor = rhs.denominator / divisor;
+        let rhs_factor = self.denominator / divisor;
+        let lhs = checked_mul(self.numerator, lhs_factor)?;
+        let rhs = checked_mul(rhs.numerator, rhs_factor)?;
+        let numerator = lhs.checked_add(rhs).ok_or(overflow_error(rhs < T::ZERO))?;
+        Self::try_new(numerator, checked_mul(self.denominator, lhs_factor)?)
+    }
+
+    /// Safely calculates self - rhs. Returns an error if the result does not fit in `T`.
+    pub fn try_sub(self, rhs: Self) -> Result<Self, FixedPointError> {
+        let divisor = self.denominator_gcd(&rhs)?;
+        let lhs_factor = rhs.denominator / divisor;
+        let rhs_factor = self.denominator / divisor;
+        let lhs = checked_mul(self.numerator, lhs_factor)?;
+        let rhs = checked_mul(rhs.numerator, rhs_factor)?;
+        let numerator = lhs.checked_sub(rhs).ok_or(overflow_error(rhs >= T::ZERO))?;
+        Self::try_new(numerator, checked_mul(self.denominator, lhs_factor)?)
+    }
+
+    /// Safely calculates self * rhs. Returns an error if the result does not fit in `T`.
+    pub fn try_mul(self, rhs: Self) -> Result<Self, FixedPointError> {
+        // cancel common factors before multiplying, to keep the intermediates small
+        let lhs_divisor = gcd_of(self.numerator, rhs.denominator)?;
+        let rhs_divisor = gcd_of(rhs.numerator, self.denominator)?;
+        let numerator = checked_mul(self.numerator / lhs_divisor, rhs.numerator / rhs_divisor)?;
+        let denominator = checked_mul(
+            self.denominator / rhs_divisor,
+            rhs.denominator / lhs_divisor,
+        )?;
+        Ok(Fraction {
+            numerator,
+            denominator,
+        })
+    }
+
+    /// Safely calculates self / rhs. Returns an error if the result does not fit in `T`
+    /// or if rhs is 0.
+    pub fn try_div(self, rhs: Self) -> Result<Self, FixedPointError> {
+        self.try_mul(rhs.try_recip()?)
+    }
+
+    /// Safely calculates 1 / self. Returns an error if the result does not fit in `T`
+    /// or if self is 0.
+    pub fn try_recip(self) -> Result<Self, FixedPointError> {
+        Self::try_new(self.denominator, self.numerator)
+    }
+
+    /// Safely calculates self + rhs. Returns None if an overflow occurs.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.try_add(rhs).ok()
+    }
+
+    /// Safely calculates self - rhs. Returns None if an overflow occurs.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.try_sub(rhs).ok()
+    }
+
+    /// Safely calculates self * rhs. Returns None if an overflow occurs.
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        self.try_mul(rhs).ok()
+    }
+
+    /// Safely calculates self / rhs. Returns None if an overflow occurs or if rhs is 0.
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.try_div(rhs).ok()
+    }
+
+    /// Safely calculates 1 / self. Returns None if an overflow occurs or if self is 0.
+    pub fn checked_recip(self) -> Option<Self> {
+        self.try_recip().ok()
+    }
+
+    /// Safely converts the fraction to a fixed-point value at `scale`, rounded in the
+    /// direction of `rounding`. Returns an error if the result does not fit in `T`.
+    pub fn try_to_fixed(self, scale: T, rounding: Rounding) -> Result<T, FixedPointError> {
+        self.numerator
+            .try_fixed_div(self.denominator, scale, rounding)
+    }
+
+    /// Safely converts the fraction to a fixed-point value at `scale`, rounded in the
+    /// direction of `rounding`. Returns None if an overflow occurs.
+    pub fn to_fixed(self, scale: T, rounding: Rounding) -> Option<T> {
+        self.try_to_fixed(scale, rounding).ok()
+    }
+
+    /// Safely converts the fraction to a fixed-point value at `scale`, rounded down.
+    /// Returns None if an overflow occurs.
+    pub fn to_fixed_floor(self, scale: T) -> Option<T> {
+        self.numerator.fixed_div_floor(self.denominator, scale)
+    }
+
+    /// Safely converts the fraction to a fixed-point value at `scale`, rounded up.
+    /// Returns None if an overflow occurs.
+    pub fn to_fixed_ceil(self, scale: T) -> Option<T> {
+        self.numerator.fixed_div_ceil(self.denominator, scale)
+    }
+
+    fn denominator_gcd(&self, rhs: &Self) -> Result<T, FixedPointError> {
+        gcd_of(self.denominator, rhs.denominator)
+    }
+}
+
+impl<T: Integer> PartialOrd for Fraction<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Integer> Ord for Fraction<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // the denominators are positive, so a/b < c/d if and only if a * d < c * b
+        let negative = self.numerator < T::ZERO;
+        if negative != (other.numerator < T::ZERO) {
+            return self.numerator.cmp(&other.numerator);
+        }
+        let lhs = cross_product(self.numerator, other.denominator);
+        let rhs = cross_product(other.numerator, self.denominator);
+        if negative {
+            rhs.cmp(&lhs)
+        } else {
+            lhs.cmp(&rhs)
+        }
+    }
+}
+
+/// Returns the magnitude of x * y, where y is positive
+fn cross_product<T: Integer>(x: T, y: T) -> U256 {
+    // every supported type has a magnitude that fits in a u128
+    U256::mul_u128(
+        magnitude(x).unwrap_or_default(),
+        magnitude(y).unwrap_or_default(),
+    )
+}
+
+/// Returns the greatest common divisor of x and y, where y is positive
+fn gcd_of<T: Integer>(x: T, y: T) -> Result<T, FixedPointError> {
+    let x = magnitude(x).ok_or(FixedPointError::Overflow)?;
+    let y = magnitude(y).ok_or(FixedPointError::Overflow)?;
+    // the divisor is at most y, so it fits in `T`
+    T::from_u128(gcd(x, y)).ok_or(FixedPointError::Overflow)
+}
+
+/// Returns the greatest common divisor of x and y with Euclid's algorithm
+fn gcd(mut x: u128, mut y: u128) -> u128 {
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    x
+}
+
+fn checked_mul<T: Integer>(x: T, y: T) -> Result<T, FixedPointError> {
+    x.checked_mul(y)
+        .ok_or(overflow_error((x < T::ZERO) != (y < T::ZERO)))
+}
+
+fn overflow_error(negative: bool) -> FixedPointError {
+    if negative {
+        FixedPointError::Underflow
+    } else {
+        FixedPointError::Overflow
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_reduces() {
+        let fraction = Fraction::new(6_i128, -8).unwrap();
+
+        assert_eq!(fraction.numerator(), -3);
+        assert_eq!(fraction.denominator(), 4);
+        assert_eq!(Fraction::new(-6_i128, -8), Fraction::new(3, 4));
+        assert_eq!(Fraction::new(0_u64, 7), Some(Fraction::from_integer(0)));
+        assert_eq!(Fraction::new(i128::MIN, i128::MIN), Fraction::new(1, 1));
+    }
+
+    #[test]
+    fn test_new_errors() {
+        assert_eq!(
+            Fraction::try_new(1_i128, 0),
+            Err(FixedPointError::DivideByZero)
+        );
+        assert_eq!(
+            Fraction::try_new(1_i128, i128::MIN),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!(
+            Fraction::try_new(i128::MIN, -1),
+            Err(FixedPointError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_add_sub() {
+        let x = Fraction::new(1_i128, 6).unwrap();
+        let y = Fraction::new(3_i128, 10).unwrap();
+
+        assert_eq!(x.checked_add(y), Fraction::new(7, 15));
+        assert_eq!(x.checked_sub(y), Fraction::new(-2, 15));
+        assert_eq!(
+            x.checked_add(Fraction::new(5, 6).unwrap()),
+            Some(Fraction::from_integer(1))
+        );
+    }
+
+    #[test]
+    fn test_add_sub_errors() {
+        let max = Fraction::from_integer(u64::MAX);
+        let one = Fraction::from_integer(1_u64);
+
+        assert_eq!(max.try_add(one), Err(FixedPointError::Overflow));
+        assert_eq!(
+            Fraction::new(1_u64, 3).unwrap().try_sub(one),
+            Err(FixedPointError::Underflow)
+        );
+        assert_eq!(
+            Fraction::from_integer(i128::MIN).try_sub(Fraction::from_integer(1)),
+            Err(FixedPointError::Underflow)
+        );
+        assert_eq!(
+            Fraction::new(1_i128, i128::MAX)
+                .unwrap()
+                .try_add(Fraction::new(1, i128::MAX - 1).unwrap()),
+            Err(FixedPointError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_mul_div() {
+        let x = Fraction::new(4_i128, 9).unwrap();
+        let y = Fraction::new(-3_i128, 8).unwrap();
+
+        assert_eq!(x.checked_mul(y), Fraction::new(-1, 6));
+        assert_eq!(x.checked_div(y), Fraction::new(-32, 27));
+        assert_eq!(y.checked_recip(), Fraction::new(-8, 3));
+        assert_eq!(
+            x.try_div(Fraction::from_integer(0)),
+            Err(FixedPointError::DivideByZero)
+        );
+    }
+
+    #[test]
+    fn test_mul_cancels_before_multiplying() {
+        let x = Fraction::new(u64::MAX, 3).unwrap();
+        let y = Fraction::new(3_u64, u64::MAX - 1).unwrap();
+
+        assert_eq!(x.checked_mul(y), Fraction::new(u64::MAX, u64::MAX - 1));
+        assert_eq!(
+            x.try_mul(Fraction::from_integer(4)),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!(
+            Fraction::from_integer(i64::MAX).try_mul(Fraction::from_integer(-2)),
+            Err(FixedPointError::Underflow)
+        );
+    }
+
+    #[test]
+    fn test_ordering() {
+        let third = Fraction::new(1_i128, 3).unwrap();
+        let half = Fraction::new(1_i128, 2).unwrap();
+
+        assert!(third < half);
+        assert!(Fraction::new(-1_i128, 2).unwrap() < Fraction::new(-1, 3).unwrap());
+        assert!(Fraction::new(-1_i128, 2).unwrap() < third);
+        assert_eq!(half.cmp(&Fraction::new(2, 4).unwrap()), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_ordering_does_not_overflow() {
+        let x = Fraction::new(i128::MAX, i128::MAX - 1).unwrap();
+        let y = Fraction::new(i128::MAX - 1, i128::MAX - 2).unwrap();
+
+        // 1 + 1/(MAX - 1) < 1 + 1/(MAX - 2)
+        assert!(x < y);
+        let x = Fraction::new(-i128::MAX, i128::MAX - 1).unwrap();
+        let y = Fraction::new(1 - i128::MAX, i128::MAX - 2).unwrap();
+        assert!(y < x);
+    }
+
+    #[test]
+    fn test_to_fixed() {
+        let fraction = Fraction::new(2_i128, 3).unwrap();
+
+        assert_eq!(fraction.to_fixed_floor(1_0000000), Some(6666666));
+        assert_eq!(fraction.to_fixed_ceil(1_0000000), Some(6666667));
+        assert_eq!(
+            fraction.to_fixed(1_0000000, Rounding::HalfUp),
+            Some(6666667)
+        );
+        assert_eq!(
+            Fraction::new(-2_i128, 3).unwrap().to_fixed_floor(1_0000000),
+            Some(-6666667)
+        );
+        assert_eq!(
+            Fraction::new(u64::MAX, 2)
+                .unwrap()
+                .try_to_fixed(10, Rounding::Floor),
+            Err(FixedPointError::Overflow)
+        );
+    }
+}
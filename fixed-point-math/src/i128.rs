@@ -1,53 +1,136 @@
-use crate::fixed_point::FixedPoint;
-
+//! The `FixedPoint` implementation for `i128`, with its routines also available as
+//! `const fn`s for protocol parameters that are computed at compile time
+
+#[allow(deprecated)]
+use crate::{
+    error::FixedPointError,
+    fixed_point::FixedPoint,
+    format,
+    rounding::{Remainder, Rounding},
+    u256::U256,
+};
+
+#[allow(deprecated)]
 impl FixedPoint for i128 {
-    fn fixed_mul_floor(self, y: i128, denominator: i128) -> Option<i128> {
-        mul_div_floor(self, y, denominator)
+    fn try_fixed_mul(
+        self,
+        y: i128,
+        denominator: i128,
+        rounding: Rounding,
+    ) -> Result<i128, FixedPointError> {
+        try_fixed_mul(self, y, denominator, rounding)
     }
+}
 
-    fn fixed_mul_ceil(self, y: i128, denominator: i128) -> Option<i128> {
-        mul_div_ceil(self, y, denominator)
+/// Safely calculates x * y / denominator, rounded in the direction of `rounding`.
+/// Returns an error if the result does not fit in an i128 or if the denominator is 0.
+///
+/// The product is computed as a 256-bit magnitude, such that only the final result can
+/// overflow.
+pub const fn try_fixed_mul(
+    x: i128,
+    y: i128,
+    denominator: i128,
+    rounding: Rounding,
+) -> Result<i128, FixedPointError> {
+    let r = U256::mul_u128(x.unsigned_abs(), y.unsigned_abs());
+    let negative = !r.is_zero() && ((x < 0) ^ (y < 0) ^ (denominator < 0));
+    let out_of_bounds = if negative {
+        FixedPointError::Underflow
+    } else {
+        FixedPointError::Overflow
+    };
+
+    let (quotient, remainder) = match r.div_rem_u128(denominator.unsigned_abs()) {
+        Some(result) => result,
+        None => return Err(FixedPointError::DivideByZero),
+    };
+    let mut quotient = match quotient.to_u128() {
+        Some(quotient) => quotient,
+        None => return Err(out_of_bounds),
+    };
+    let remainder = Remainder::from_u128(remainder, denominator.unsigned_abs());
+    if rounding.round_away_from_zero(negative, quotient % 2 == 1, remainder) {
+        quotient = match quotient.checked_add(1) {
+            Some(quotient) => quotient,
+            None => return Err(out_of_bounds),
+        };
     }
-
-    fn fixed_div_floor(self, y: i128, denominator: i128) -> Option<i128> {
-        mul_div_floor(self, denominator, y)
+    match to_i128(negative, quotient) {
+        Some(result) => Ok(result),
+        None => Err(out_of_bounds),
     }
+}
 
-    fn fixed_div_ceil(self, y: i128, denominator: i128) -> Option<i128> {
-        mul_div_ceil(self, denominator, y)
-    }
+/// Safely calculates x * denominator / y, rounded in the direction of `rounding`.
+/// Returns an error if the result does not fit in an i128 or if y is 0.
+pub const fn try_fixed_div(
+    x: i128,
+    y: i128,
+    denominator: i128,
+    rounding: Rounding,
+) -> Result<i128, FixedPointError> {
+    try_fixed_mul(x, denominator, y, rounding)
 }
 
-// @dev TODO: Can optimize once `div_floor` and `div_ceil` are stable
-//            https://github.com/rust-lang/rust/issues/88581
+/// Safely calculates floor(x * y / denominator). Returns None if the result overflows or
+/// if the denominator is 0.
+pub const fn fixed_mul_floor(x: i128, y: i128, denominator: i128) -> Option<i128> {
+    ok(try_fixed_mul(x, y, denominator, Rounding::Floor))
+}
 
-/// Performs floor(x * y / z)
-fn mul_div_floor(x: i128, y: i128, z: i128) -> Option<i128> {
-    let r = x.checked_mul(y)?;
-    if r < 0 || (r > 0 && z < 0) {
-        // ceiling is taken by default for a negative result
-        let remainder = r.checked_rem_euclid(z)?;
-        (r / z).checked_sub(if remainder > 0 { 1 } else { 0 })
-    } else {
-        // floor taken by default for a positive or zero result
-        r.checked_div(z)
+/// Safely calculates ceil(x * y / denominator). Returns None if the result overflows or
+/// if the denominator is 0.
+pub const fn fixed_mul_ceil(x: i128, y: i128, denominator: i128) -> Option<i128> {
+    ok(try_fixed_mul(x, y, denominator, Rounding::Ceil))
+}
+
+/// Safely calculates floor(x * denominator / y). Returns None if the result overflows or
+/// if y is 0.
+pub const fn fixed_div_floor(x: i128, y: i128, denominator: i128) -> Option<i128> {
+    ok(try_fixed_div(x, y, denominator, Rounding::Floor))
+}
+
+/// Safely calculates ceil(x * denominator / y). Returns None if the result overflows or
+/// if y is 0.
+pub const fn fixed_div_ceil(x: i128, y: i128, denominator: i128) -> Option<i128> {
+    ok(try_fixed_div(x, y, denominator, Rounding::Ceil))
+}
+
+/// Parses a decimal string, like "-1.25", into a fixed-point number with `decimals`
+/// decimal places. Returns None if the string is malformed, if it has more fractional
+/// digits than `decimals`, or if the result overflows.
+///
+/// Accepts the same strings as `FixedPoint::parse_fixed`, including underscores after a
+/// digit, and is usable in `const` contexts. This is used by the `fixed!` macro.
+pub const fn parse_fixed(s: &str, decimals: u32) -> Option<i128> {
+    match format::parse_magnitude(s, decimals, None) {
+        Ok((negative, magnitude)) => to_i128(negative, magnitude),
+        Err(_) => None,
     }
 }
 
-/// Performs ceil(x * y / z)
-fn mul_div_ceil(x: i128, y: i128, z: i128) -> Option<i128> {
-    let r = x.checked_mul(y)?;
-    if r <= 0 || (r > 0 && z < 0) {
-        // ceiling is taken by default for a negative or zero result
-        r.checked_div(z)
+/// Converts a sign and magnitude into an i128, or None if it does not fit
+const fn to_i128(negative: bool, magnitude: u128) -> Option<i128> {
+    if negative {
+        0_i128.checked_sub_unsigned(magnitude)
+    } else if magnitude <= i128::MAX as u128 {
+        Some(magnitude as i128)
     } else {
-        // floor taken by default for a positive result
-        let remainder = r.checked_rem_euclid(z)?;
-        (r / z).checked_add(if remainder > 0 { 1 } else { 0 })
+        None
+    }
+}
+
+/// Converts a result into an option, usable in `const` contexts
+const fn ok(result: Result<i128, FixedPointError>) -> Option<i128> {
+    match result {
+        Ok(value) => Some(value),
+        Err(_) => None,
     }
 }
 
 #[cfg(test)]
+#[allow(deprecated)]
 mod tests {
     use super::*;
 
@@ -87,11 +170,44 @@ mod tests {
     }
 
     #[test]
-    fn test_fixed_mul_floor_phantom_overflow() {
+    fn test_fixed_mul_floor_phantom_overflow_uses_u256() {
         let x: i128 = 170_141_183_460_469_231_731;
         let y: i128 = 1_000_000_000_000_000_001;
         let denominator: i128 = 1_000_000_000_000_000_000;
 
+        let result = x.fixed_mul_floor(y, denominator).unwrap();
+
+        assert_eq!(result, 170_141_183_460_469_231_901);
+    }
+
+    #[test]
+    fn test_fixed_mul_floor_negative_phantom_overflow_uses_u256() {
+        let x: i128 = -170_141_183_460_469_231_731;
+        let y: i128 = 1_000_000_000_000_000_001;
+        let denominator: i128 = 1_000_000_000_000_000_000;
+
+        let result = x.fixed_mul_floor(y, denominator).unwrap();
+
+        assert_eq!(result, -170_141_183_460_469_231_902);
+    }
+
+    #[test]
+    fn test_fixed_mul_floor_min_value() {
+        let x: i128 = i128::MIN;
+        let y: i128 = 2;
+        let denominator: i128 = 2;
+
+        let result = x.fixed_mul_floor(y, denominator).unwrap();
+
+        assert_eq!(result, i128::MIN);
+    }
+
+    #[test]
+    fn test_fixed_mul_floor_result_overflow() {
+        let x: i128 = 170_141_183_460_469_231_731_000_000_000_000_000_000;
+        let y: i128 = 2_000_000_000_000_000_000;
+        let denominator: i128 = 1_000_000_000_000_000_000;
+
         let result = x.fixed_mul_floor(y, denominator);
 
         assert_eq!(None, result);
@@ -133,11 +249,33 @@ mod tests {
     }
 
     #[test]
-    fn test_fixed_mul_ceil_phantom_overflow() {
+    fn test_fixed_mul_ceil_phantom_overflow_uses_u256() {
         let x: i128 = 170_141_183_460_469_231_731;
         let y: i128 = 1_000_000_000_000_000_001;
         let denominator: i128 = 1_000_000_000_000_000_000;
 
+        let result = x.fixed_mul_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, 170_141_183_460_469_231_902);
+    }
+
+    #[test]
+    fn test_fixed_mul_ceil_negative_phantom_overflow_uses_u256() {
+        let x: i128 = -170_141_183_460_469_231_731;
+        let y: i128 = 1_000_000_000_000_000_001;
+        let denominator: i128 = 1_000_000_000_000_000_000;
+
+        let result = x.fixed_mul_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, -170_141_183_460_469_231_901);
+    }
+
+    #[test]
+    fn test_fixed_mul_ceil_result_overflow() {
+        let x: i128 = 170_141_183_460_469_231_731_000_000_000_000_000_000;
+        let y: i128 = 2_000_000_000_000_000_000;
+        let denominator: i128 = 1_000_000_000_000_000_000;
+
         let result = x.fixed_mul_ceil(y, denominator);
 
         assert_eq!(None, result);
@@ -179,11 +317,33 @@ mod tests {
     }
 
     #[test]
-    fn test_fixed_div_floor_phantom_overflow() {
+    fn test_fixed_div_floor_phantom_overflow_uses_u256() {
         let x: i128 = 170_141_183_460_469_231_732;
         let y: i128 = 1_000_000_000_000_000_000;
         let denominator: i128 = 1_000_000_000_000_000_000;
 
+        let result = x.fixed_div_floor(y, denominator).unwrap();
+
+        assert_eq!(result, 170_141_183_460_469_231_732);
+    }
+
+    #[test]
+    fn test_fixed_div_floor_result_overflow() {
+        let x: i128 = 170_141_183_460_469_231_731_000_000_000_000_000_000;
+        let y: i128 = 2_000_000_000_000_000_000;
+        let denominator: i128 = 4_000_000_000_000_000_000;
+
+        let result = x.fixed_div_floor(y, denominator);
+
+        assert_eq!(None, result);
+    }
+
+    #[test]
+    fn test_fixed_div_floor_divide_by_zero() {
+        let x: i128 = 1_0000000;
+        let y: i128 = 0;
+        let denominator: i128 = 1_0000000;
+
         let result = x.fixed_div_floor(y, denominator);
 
         assert_eq!(None, result);
@@ -225,13 +385,331 @@ mod tests {
     }
 
     #[test]
-    fn test_fixed_div_ceil_phantom_overflow() {
+    fn test_fixed_div_ceil_phantom_overflow_uses_u256() {
         let x: i128 = 170_141_183_460_469_231_732;
         let y: i128 = 1_000_000_000_000_000_000;
         let denominator: i128 = 1_000_000_000_000_000_000;
 
+        let result = x.fixed_div_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, 170_141_183_460_469_231_732);
+    }
+
+    #[test]
+    fn test_fixed_div_ceil_result_overflow() {
+        let x: i128 = 170_141_183_460_469_231_731_000_000_000_000_000_000;
+        let y: i128 = 2_000_000_000_000_000_000;
+        let denominator: i128 = 4_000_000_000_000_000_000;
+
         let result = x.fixed_div_ceil(y, denominator);
 
         assert_eq!(None, result);
     }
+
+    #[test]
+    fn test_fixed_div_ceil_divide_by_zero() {
+        let x: i128 = 1_0000000;
+        let y: i128 = 0;
+        let denominator: i128 = 1_0000000;
+
+        let result = x.fixed_div_ceil(y, denominator);
+
+        assert_eq!(None, result);
+    }
+
+    /********** fixed_mul **********/
+
+    #[test]
+    fn test_fixed_mul_toward_and_away_from_zero() {
+        let x: i128 = -1_5391283;
+        let y: i128 = 314_1592653;
+        let denominator: i128 = 1_0000001;
+
+        let toward = x.fixed_mul(y, denominator, Rounding::TowardZero).unwrap();
+        let away = x.fixed_mul(y, denominator, Rounding::AwayFromZero).unwrap();
+
+        assert_eq!(toward, -483_5313675);
+        assert_eq!(away, -483_5313676);
+    }
+
+    #[test]
+    fn test_fixed_mul_half_up_rounds_to_nearest() {
+        let x: i128 = 5;
+        let y: i128 = 9_999_999;
+        let denominator: i128 = 2_0000000;
+
+        let result = x.fixed_mul(y, denominator, Rounding::HalfUp).unwrap();
+
+        assert_eq!(result, 2)
+    }
+
+    #[test]
+    fn test_fixed_mul_half_up_ties_away_from_zero() {
+        let x: i128 = 5;
+        let y: i128 = 1_0000000;
+        let denominator: i128 = 2_0000000;
+
+        let result = x.fixed_mul(y, denominator, Rounding::HalfUp).unwrap();
+
+        assert_eq!(result, 3)
+    }
+
+    #[test]
+    fn test_fixed_mul_half_up_negative_ties_away_from_zero() {
+        let x: i128 = -5;
+        let y: i128 = 1_0000000;
+        let denominator: i128 = 2_0000000;
+
+        let result = x.fixed_mul(y, denominator, Rounding::HalfUp).unwrap();
+
+        assert_eq!(result, -3)
+    }
+
+    #[test]
+    fn test_fixed_mul_half_even_ties_to_even() {
+        let x: i128 = 5;
+        let y: i128 = 1_0000000;
+        let denominator: i128 = 2_0000000;
+
+        let result = x.fixed_mul(y, denominator, Rounding::HalfEven).unwrap();
+
+        assert_eq!(result, 2)
+    }
+
+    #[test]
+    fn test_fixed_mul_half_even_odd_ties_to_even() {
+        let x: i128 = 7;
+        let y: i128 = 1_0000000;
+        let denominator: i128 = 2_0000000;
+
+        let result = x.fixed_mul(y, denominator, Rounding::HalfEven).unwrap();
+
+        assert_eq!(result, 4)
+    }
+
+    #[test]
+    fn test_fixed_mul_half_even_negative_ties_to_even() {
+        let x: i128 = -5;
+        let y: i128 = 1_0000000;
+        let denominator: i128 = 2_0000000;
+
+        let result = x.fixed_mul(y, denominator, Rounding::HalfEven).unwrap();
+
+        assert_eq!(result, -2)
+    }
+
+    #[test]
+    fn test_fixed_mul_half_even_rounds_to_nearest() {
+        let x: i128 = 5;
+        let y: i128 = 1_0000001;
+        let denominator: i128 = 2_0000000;
+
+        let result = x.fixed_mul(y, denominator, Rounding::HalfEven).unwrap();
+
+        assert_eq!(result, 3)
+    }
+
+    #[test]
+    fn test_fixed_mul_half_even_large_number() {
+        let x: i128 = -170_141_183_460_469_231_731;
+        let y: i128 = 1_000_000_000_000_000_000;
+        let denominator: i128 = 2_000_000_000_000_000_000;
+
+        let result = x.fixed_mul(y, denominator, Rounding::HalfEven).unwrap();
+
+        assert_eq!(result, -85_070_591_730_234_615_866);
+    }
+
+    #[test]
+    fn test_fixed_mul_rounding_overflow() {
+        // x * y = 2 * i128::MAX + 1
+        let x: i128 = 18_446_744_073_709_551_615;
+        let y: i128 = 18_446_744_073_709_551_617;
+        let denominator: i128 = 2;
+
+        let truncated = x.fixed_mul(y, denominator, Rounding::TowardZero);
+        let rounded = x.fixed_mul(y, denominator, Rounding::HalfUp);
+
+        assert_eq!(truncated, Some(i128::MAX));
+        assert_eq!(rounded, None);
+    }
+
+    /********** fixed_div **********/
+
+    #[test]
+    fn test_fixed_div_half_up() {
+        let x: i128 = 314_1592653;
+        let y: i128 = -1_5391280;
+        let denominator: i128 = 1_0000000;
+
+        let result = x.fixed_div(y, denominator, Rounding::HalfUp).unwrap();
+
+        assert_eq!(result, -204_1150998)
+    }
+
+    #[test]
+    fn test_fixed_div_half_even() {
+        let x: i128 = 5;
+        let y: i128 = 2_0000000;
+        let denominator: i128 = 1_0000000;
+
+        let result = x.fixed_div(y, denominator, Rounding::HalfEven).unwrap();
+
+        assert_eq!(result, 2)
+    }
+
+    /********** try_fixed_mul **********/
+
+    #[test]
+    fn test_try_fixed_mul_divide_by_zero() {
+        let result = 1_0000000_i128.try_fixed_mul(1_0000000, 0, Rounding::Floor);
+
+        assert_eq!(result, Err(FixedPointError::DivideByZero));
+    }
+
+    #[test]
+    fn test_try_fixed_mul_overflow() {
+        let result = i128::MAX.try_fixed_mul(2, 1, Rounding::Floor);
+
+        assert_eq!(result, Err(FixedPointError::Overflow));
+    }
+
+    #[test]
+    fn test_try_fixed_mul_underflow() {
+        let result = i128::MAX.try_fixed_mul(-2, 1, Rounding::Floor);
+
+        assert_eq!(result, Err(FixedPointError::Underflow));
+    }
+
+    #[test]
+    fn test_try_fixed_mul_rounding_underflow() {
+        // x * y = -(2^128 + 1)
+        let x: i128 = -59_649_589_127_497_217;
+        let y: i128 = 5_704_689_200_685_129_054_721;
+        let denominator: i128 = 2;
+
+        let truncated = x.try_fixed_mul(y, denominator, Rounding::TowardZero);
+        let floored = x.try_fixed_mul(y, denominator, Rounding::Floor);
+
+        assert_eq!(truncated, Ok(i128::MIN));
+        assert_eq!(floored, Err(FixedPointError::Underflow));
+    }
+
+    #[test]
+    fn test_try_fixed_div_divide_by_zero() {
+        let result = 1_0000000_i128.try_fixed_div_ceil(0, 1_0000000);
+
+        assert_eq!(result, Err(FixedPointError::DivideByZero));
+    }
+
+    /********** const **********/
+
+    #[test]
+    fn test_const_fixed_mul() {
+        const COLLATERAL_FACTOR: i128 = 8000000;
+        const LIQUIDATION_LIMIT: Option<i128> =
+            fixed_mul_floor(1_5391283, COLLATERAL_FACTOR, 1_0000000);
+        const CEIL: Option<i128> = fixed_mul_ceil(-1_5391283, COLLATERAL_FACTOR, 1_0000000);
+
+        assert_eq!(LIQUIDATION_LIMIT, Some(1_2313026));
+        assert_eq!(CEIL, Some(-1_2313026));
+        assert_eq!(
+            fixed_mul_floor(-1_5391283, COLLATERAL_FACTOR, 1_0000000),
+            Some(-1_2313027)
+        );
+        assert_eq!(fixed_mul_floor(1, 1, 0), None);
+    }
+
+    #[test]
+    fn test_const_fixed_div() {
+        const PRICE: Option<i128> = fixed_div_floor(1_0000000, 3_0000000, 1_0000000);
+
+        assert_eq!(PRICE, Some(3333333));
+        assert_eq!(
+            fixed_div_ceil(1_0000000, 3_0000000, 1_0000000),
+            Some(3333334)
+        );
+        assert_eq!(
+            try_fixed_div(1, 0, 1_0000000, Rounding::Floor),
+            Err(FixedPointError::DivideByZero)
+        );
+    }
+
+    #[test]
+    fn test_const_matches_trait() {
+        let values = [i128::MIN, -1_5391283, -1, 0, 1, 314_1592653, i128::MAX];
+        let roundings = [Rounding::Floor, Rounding::HalfEven, Rounding::AwayFromZero];
+
+        for x in values {
+            for y in values {
+                for rounding in roundings {
+                    assert_eq!(
+                        try_fixed_mul(x, y, 1_0000001, rounding),
+                        x.try_fixed_mul(y, 1_0000001, rounding)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_fixed() {
+        const FACTOR: Option<i128> = parse_fixed("0.8", 7);
+
+        assert_eq!(FACTOR, Some(8000000));
+        assert_eq!(parse_fixed("-1.25", 7), Some(-1_2500000));
+        assert_eq!(parse_fixed("+1_000.000_1", 7), Some(1000_0001000));
+        assert_eq!(parse_fixed("42", 0), Some(42));
+        assert_eq!(parse_fixed("0", 40), Some(0));
+        assert_eq!(
+            parse_fixed("-170141183460469231731687303715884105728", 0),
+            Some(i128::MIN)
+        );
+    }
+
+    #[test]
+    fn test_parse_fixed_invalid() {
+        for s in [
+            "",
+            "-",
+            ".5",
+            "1.",
+            "1.2.3",
+            "_1",
+            "1._5",
+            "1e5",
+            "0.8f64",
+            "1.23456789",
+        ] {
+            assert_eq!(parse_fixed(s, 7), None, "{}", s);
+        }
+        assert_eq!(
+            parse_fixed("170141183460469231731687303715884105728", 0),
+            None
+        );
+        assert_eq!(parse_fixed("1", 40), None);
+    }
+
+    #[test]
+    fn test_fixed_macro() {
+        const COLLATERAL_FACTOR: i128 = crate::fixed!(0.8, 7);
+        const MIN_PRICE: i128 = crate::fixed!(-0.000_001, crate::STROOP_DECIMALS);
+        const FEE: u64 = crate::fixed!(0.003, 7, u64);
+        const LIMIT: u32 = crate::fixed!(42, 0, u32);
+
+        assert_eq!(COLLATERAL_FACTOR, 8000000);
+        assert_eq!(MIN_PRICE, -10);
+        assert_eq!(FEE, 30000);
+        assert_eq!(LIMIT, 42);
+        assert_eq!(crate::fixed!(1_000_000, 18), 1_000_000 * 10_i128.pow(18));
+    }
+
+    #[test]
+    fn test_fixed_macro_u128() {
+        // 3 * 10^38 is above i128::MAX
+        const SUPPLY: u128 = crate::fixed!(300_000_000_000_000_000_000, 18, u128);
+
+        assert_eq!(SUPPLY, 3 * 10_u128.pow(38));
+        assert_eq!(crate::fixed!(0.5, 7, u128), 5000000);
+    }
 }
@@ -0,0 +1,253 @@
+impl_fixed_point_widened!(signed i32, i64);
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use crate::{error::FixedPointError, fixed_point::FixedPoint};
+
+    /********** fixed_mul_floor **********/
+
+    #[test]
+    fn test_fixed_mul_floor_rounds_down() {
+        let x: i32 = 1_5391;
+        let y: i32 = 314_1592;
+        let denominator: i32 = 1_0001;
+
+        let result = x.fixed_mul_floor(y, denominator).unwrap();
+
+        assert_eq!(result, 483_4740);
+    }
+
+    #[test]
+    fn test_fixed_mul_floor_negative_rounds_down() {
+        let x: i32 = -1_5391;
+        let y: i32 = 314_1592;
+        let denominator: i32 = 1_0001;
+
+        let result = x.fixed_mul_floor(y, denominator).unwrap();
+
+        assert_eq!(result, -483_4741);
+    }
+
+    #[test]
+    fn test_fixed_mul_floor_large_number() {
+        let x: i32 = 2_147_483;
+        let y: i32 = 1_000;
+        let denominator: i32 = 1_000;
+
+        let result = x.fixed_mul_floor(y, denominator).unwrap();
+
+        assert_eq!(result, 2_147_483);
+    }
+
+    #[test]
+    fn test_fixed_mul_floor_phantom_overflow_uses_i64() {
+        let x: i32 = 2_147_483;
+        let y: i32 = 2_000;
+        let denominator: i32 = 1_000;
+
+        let result = x.fixed_mul_floor(y, denominator).unwrap();
+
+        assert_eq!(result, 4_294_966);
+    }
+
+    #[test]
+    fn test_fixed_mul_floor_result_overflow() {
+        let x: i32 = 2_147_483_000;
+        let y: i32 = 2_000;
+        let denominator: i32 = 1_000;
+
+        let result = x.fixed_mul_floor(y, denominator);
+
+        assert_eq!(result, None);
+    }
+
+    /********** fixed_mul_ceil **********/
+
+    #[test]
+    fn test_fixed_mul_ceil_rounds_up() {
+        let x: i32 = 1_5391;
+        let y: i32 = 314_1592;
+        let denominator: i32 = 1_0001;
+
+        let result = x.fixed_mul_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, 483_4741);
+    }
+
+    #[test]
+    fn test_fixed_mul_ceil_negative_rounds_up() {
+        let x: i32 = -1_5391;
+        let y: i32 = 314_1592;
+        let denominator: i32 = 1_0001;
+
+        let result = x.fixed_mul_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, -483_4740);
+    }
+
+    #[test]
+    fn test_fixed_mul_ceil_large_number() {
+        let x: i32 = 2_147_483;
+        let y: i32 = 1_000;
+        let denominator: i32 = 1_000;
+
+        let result = x.fixed_mul_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, 2_147_483);
+    }
+
+    #[test]
+    fn test_fixed_mul_ceil_phantom_overflow_uses_i64() {
+        let x: i32 = 2_147_483;
+        let y: i32 = 2_000;
+        let denominator: i32 = 1_000;
+
+        let result = x.fixed_mul_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, 4_294_966);
+    }
+
+    #[test]
+    fn test_fixed_mul_ceil_result_overflow() {
+        let x: i32 = 2_147_483_000;
+        let y: i32 = 2_000;
+        let denominator: i32 = 1_000;
+
+        let result = x.fixed_mul_ceil(y, denominator);
+
+        assert_eq!(result, None);
+    }
+
+    /********** fixed_div_floor **********/
+
+    #[test]
+    fn test_fixed_div_floor_rounds_down() {
+        let x: i32 = 314_1592;
+        let y: i32 = 1_5391;
+        let denominator: i32 = 1_0000;
+
+        let result = x.fixed_div_floor(y, denominator).unwrap();
+
+        assert_eq!(result, 204_1187);
+    }
+
+    #[test]
+    fn test_fixed_div_floor_negative_rounds_down() {
+        let x: i32 = 314_1592;
+        let y: i32 = -1_5391;
+        let denominator: i32 = 1_0000;
+
+        let result = x.fixed_div_floor(y, denominator).unwrap();
+
+        assert_eq!(result, -204_1188);
+    }
+
+    #[test]
+    fn test_fixed_div_floor_large_number() {
+        let x: i32 = 2_147_483;
+        let y: i32 = 1_000;
+        let denominator: i32 = 1_000;
+
+        let result = x.fixed_div_floor(y, denominator).unwrap();
+
+        assert_eq!(result, 2_147_483);
+    }
+
+    #[test]
+    fn test_fixed_div_floor_phantom_overflow_uses_i64() {
+        let x: i32 = 2_147_485;
+        let y: i32 = 2_000;
+        let denominator: i32 = 1_000;
+
+        let result = x.fixed_div_floor(y, denominator).unwrap();
+
+        assert_eq!(result, 1_073_742);
+    }
+
+    #[test]
+    fn test_fixed_div_floor_result_overflow() {
+        let x: i32 = 2_147_483_000;
+        let y: i32 = 2_000;
+        let denominator: i32 = 4_000;
+
+        let result = x.fixed_div_floor(y, denominator);
+
+        assert_eq!(result, None);
+    }
+
+    /********** fixed_div_ceil **********/
+
+    #[test]
+    fn test_fixed_div_ceil_rounds_up() {
+        let x: i32 = 314_1592;
+        let y: i32 = 1_5391;
+        let denominator: i32 = 1_0000;
+
+        let result = x.fixed_div_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, 204_1188);
+    }
+
+    #[test]
+    fn test_fixed_div_ceil_negative_rounds_up() {
+        let x: i32 = 314_1592;
+        let y: i32 = -1_5391;
+        let denominator: i32 = 1_0000;
+
+        let result = x.fixed_div_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, -204_1187);
+    }
+
+    #[test]
+    fn test_fixed_div_ceil_large_number() {
+        let x: i32 = 2_147_483;
+        let y: i32 = 1_000;
+        let denominator: i32 = 1_000;
+
+        let result = x.fixed_div_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, 2_147_483);
+    }
+
+    #[test]
+    fn test_fixed_div_ceil_phantom_overflow_uses_i64() {
+        let x: i32 = 2_147_485;
+        let y: i32 = 2_000;
+        let denominator: i32 = 1_000;
+
+        let result = x.fixed_div_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, 1_073_743);
+    }
+
+    #[test]
+    fn test_fixed_div_ceil_result_overflow() {
+        let x: i32 = 2_147_483_000;
+        let y: i32 = 2_000;
+        let denominator: i32 = 4_000;
+
+        let result = x.fixed_div_ceil(y, denominator);
+
+        assert_eq!(result, None);
+    }
+
+    /********** try_fixed_mul **********/
+
+    #[test]
+    fn test_try_fixed_mul_errors() {
+        assert_eq!(
+            1_i32.try_fixed_mul_floor(1, 0),
+            Err(FixedPointError::DivideByZero)
+        );
+        assert_eq!(
+            i32::MAX.try_fixed_mul_floor(2, 1),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!(
+            i32::MIN.try_fixed_mul_floor(2, 1),
+            Err(FixedPointError::Underflow)
+        );
+    }
+}
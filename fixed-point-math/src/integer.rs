@@ -0,0 +1,117 @@
+use core::ops::{Add, Div, Mul, Rem, Sub};
+
+/// Primitive integer operations used by the fixed-point types built on top of
+/// `FixedPoint`.
+pub trait Integer:
+    Copy
+    + Ord
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const TEN: Self;
+    const MIN: Self;
+    const MAX: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+
+    fn checked_pow(self, exp: u32) -> Option<Self>;
+
+    fn wrapping_add(self, rhs: Self) -> Self;
+
+    fn wrapping_sub(self, rhs: Self) -> Self;
+
+    fn wrapping_mul(self, rhs: Self) -> Self;
+
+    fn to_i128(self) -> Option<i128>;
+
+    fn from_i128(value: i128) -> Option<Self>;
+
+    fn to_u128(self) -> Option<u128>;
+
+    fn from_u128(value: u128) -> Option<Self>;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const ZERO: $t = 0;
+                const ONE: $t = 1;
+                const TEN: $t = 10;
+                const MIN: $t = <$t>::MIN;
+                const MAX: $t = <$t>::MAX;
+
+                fn checked_add(self, rhs: $t) -> Option<$t> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                fn checked_sub(self, rhs: $t) -> Option<$t> {
+                    <$t>::checked_sub(self, rhs)
+                }
+
+                fn checked_mul(self, rhs: $t) -> Option<$t> {
+                    <$t>::checked_mul(self, rhs)
+                }
+
+                fn checked_pow(self, exp: u32) -> Option<$t> {
+                    <$t>::checked_pow(self, exp)
+                }
+
+                fn wrapping_add(self, rhs: $t) -> $t {
+                    <$t>::wrapping_add(self, rhs)
+                }
+
+                fn wrapping_sub(self, rhs: $t) -> $t {
+                    <$t>::wrapping_sub(self, rhs)
+                }
+
+                fn wrapping_mul(self, rhs: $t) -> $t {
+                    <$t>::wrapping_mul(self, rhs)
+                }
+
+                fn to_i128(self) -> Option<i128> {
+                    i128::try_from(self).ok()
+                }
+
+                fn from_i128(value: i128) -> Option<$t> {
+                    <$t>::try_from(value).ok()
+                }
+
+                fn to_u128(self) -> Option<u128> {
+                    u128::try_from(self).ok()
+                }
+
+                fn from_u128(value: u128) -> Option<$t> {
+                    <$t>::try_from(value).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i32, i64, i128, u32, u64, u128);
+
+/// Returns the magnitude of x as a u128. Every supported type fits.
+pub(crate) fn magnitude<T: Integer>(x: T) -> Option<u128> {
+    x.to_u128().or_else(|| x.to_i128().map(i128::unsigned_abs))
+}
+
+/// Creates a value from its sign and magnitude. Returns None if it does not fit in `T`.
+pub(crate) fn from_magnitude<T: Integer>(negative: bool, magnitude: u128) -> Option<T> {
+    if negative {
+        0_i128
+            .checked_sub_unsigned(magnitude)
+            .and_then(T::from_i128)
+    } else {
+        T::from_u128(magnitude)
+    }
+}
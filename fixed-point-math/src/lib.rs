@@ -1,9 +1,55 @@
 #![no_std]
 
 pub const STROOP: u64 = 1_0000000;
+/// The number of decimal places in a `STROOP` scaled value
+pub const STROOP_DECIMALS: u32 = 7;
 
+#[macro_use]
+mod macros;
+
+pub mod aggregate;
+pub mod cpmm;
+pub mod curve;
 pub mod i128;
+pub mod i32;
+pub mod i64;
+pub mod rates;
+pub mod stableswap;
+pub mod twap;
+pub mod u128;
+pub mod u32;
 pub mod u64;
 
+pub mod vault;
+
+mod exp_log;
+mod mul_mul_div;
+mod rescale;
+mod u256;
+
+mod error;
+pub use error::FixedPointError;
+
 mod fixed_point;
+#[allow(deprecated)]
 pub use fixed_point::FixedPoint;
+
+mod rounding;
+pub use rounding::Rounding;
+
+mod integer;
+pub use integer::Integer;
+
+mod fixed;
+pub use fixed::Fixed;
+
+mod fraction;
+pub use fraction::Fraction;
+
+mod format;
+pub use format::FixedDisplay;
+
+#[cfg(feature = "soroban")]
+mod soroban;
+#[cfg(feature = "soroban")]
+pub use soroban::SorobanFixedPoint;
@@ -0,0 +1,131 @@
+/// Implements `FixedPoint` for a primitive integer by performing the intermediate
+/// multiplication in a type twice as wide, such that only the final result can
+/// overflow.
+///
+/// Usage: `impl_fixed_point_widened!(signed i32, i64)` or
+/// `impl_fixed_point_widened!(unsigned u32, u64)`
+macro_rules! impl_fixed_point_widened {
+    (signed $t:ty, $wide:ty) => {
+        impl_fixed_point_widened!(@impl $t);
+
+        /// Performs x * y / z, rounded in the direction of `rounding`
+        fn mul_div(
+            x: $t,
+            y: $t,
+            z: $t,
+            rounding: $crate::rounding::Rounding,
+        ) -> Result<$t, $crate::error::FixedPointError> {
+            if z == 0 {
+                return Err($crate::error::FixedPointError::DivideByZero);
+            }
+            // the product of two values cannot overflow the wide type
+            let r = <$wide>::from(x) * <$wide>::from(y);
+            let z = <$wide>::from(z);
+            let mut quotient = r / z;
+            let remainder = r % z;
+            // the sign of the exact result only matters when the remainder is non-zero
+            let negative = (remainder < 0) != (z < 0);
+            let remainder = $crate::rounding::Remainder::new(remainder.abs(), z.abs());
+            if rounding.round_away_from_zero(negative, quotient % 2 != 0, remainder) {
+                quotient += if negative { -1 } else { 1 };
+            }
+            <$t>::try_from(quotient).map_err(|_| {
+                if quotient < 0 {
+                    $crate::error::FixedPointError::Underflow
+                } else {
+                    $crate::error::FixedPointError::Overflow
+                }
+            })
+        }
+    };
+
+    (unsigned $t:ty, $wide:ty) => {
+        impl_fixed_point_widened!(@impl $t);
+
+        /// Performs x * y / z, rounded in the direction of `rounding`
+        fn mul_div(
+            x: $t,
+            y: $t,
+            z: $t,
+            rounding: $crate::rounding::Rounding,
+        ) -> Result<$t, $crate::error::FixedPointError> {
+            if z == 0 {
+                return Err($crate::error::FixedPointError::DivideByZero);
+            }
+            // the product of two values cannot overflow the wide type
+            let r = <$wide>::from(x) * <$wide>::from(y);
+            let z = <$wide>::from(z);
+            let mut quotient = r / z;
+            let remainder = $crate::rounding::Remainder::new(r % z, z);
+            if rounding.round_away_from_zero(false, quotient % 2 == 1, remainder) {
+                quotient += 1;
+            }
+            <$t>::try_from(quotient).map_err(|_| $crate::error::FixedPointError::Overflow)
+        }
+    };
+
+    (@impl $t:ty) => {
+        #[allow(deprecated)]
+        impl $crate::fixed_point::FixedPoint for $t {
+            fn try_fixed_mul(
+                self,
+                y: $t,
+                denominator: $t,
+                rounding: $crate::rounding::Rounding,
+            ) -> Result<$t, $crate::error::FixedPointError> {
+                mul_div(self, y, denominator, rounding)
+            }
+        }
+    };
+}
+
+/// Parses a decimal literal into a fixed-point number with the given number of decimal
+/// places at compile time. The value is an `i128` unless a type is given.
+///
+/// Usage: `fixed!(0.8, 7)` or `fixed!(0.8, STROOP_DECIMALS, u64)`
+///
+/// Values above `i128::MAX` can be written for `u128`. A literal that is malformed, has
+/// nonzero fractional digits beyond the decimal places, or does not fit in the type fails
+/// the build:
+///
+/// ```compile_fail
+/// const COLLATERAL_FACTOR: i128 = fixed_point_math::fixed!(0.12345678, 7);
+/// ```
+#[macro_export]
+macro_rules! fixed {
+    ($value:literal, $decimals:expr) => {
+        $crate::fixed!($value, $decimals, i128)
+    };
+
+    ($value:literal, $decimals:expr, $t:ty) => {{
+        #[allow(clippy::unnecessary_cast)]
+        const VALUE: $t = {
+            let literal = stringify!($value);
+            let out_of_range = concat!("fixed-point literal out of range ", stringify!($value));
+            match $crate::i128::parse_fixed(literal, $decimals) {
+                Some(value) => {
+                    // the value must survive a round trip through the type, and negative
+                    // values cannot be stored in unsigned types
+                    if value as $t as i128 != value || (value < 0 && <$t>::MIN as i128 == 0) {
+                        panic!("{}", out_of_range);
+                    }
+                    value as $t
+                }
+                // values above i128::MAX can only be stored in an unsigned type
+                None => match $crate::u128::parse_fixed(literal, $decimals) {
+                    Some(value) => {
+                        if value as $t as u128 != value || <$t>::MIN as i128 != 0 {
+                            panic!("{}", out_of_range);
+                        }
+                        value as $t
+                    }
+                    None => panic!(concat!(
+                        "invalid or out of range fixed-point literal ",
+                        stringify!($value)
+                    )),
+                },
+            }
+        };
+        VALUE
+    }};
+}
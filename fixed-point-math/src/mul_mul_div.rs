@@ -0,0 +1,190 @@
+use crate::{
+    error::FixedPointError,
+    integer::{from_magnitude, magnitude, Integer},
+    rounding::{Remainder, Rounding},
+    u256::U256,
+};
+
+/// Performs x * y * z / (d1 * d2), rounded once in the direction of `rounding`
+///
+/// x * y is held in 256 bits and divided by d1 before it is multiplied by z, keeping
+/// the remainder of each division, so only the final result can overflow.
+pub(crate) fn mul_mul_div<T: Integer>(
+    x: T,
+    y: T,
+    z: T,
+    d1: T,
+    d2: T,
+    rounding: Rounding,
+) -> Result<T, FixedPointError> {
+    if d1 == T::ZERO || d2 == T::ZERO {
+        return Err(FixedPointError::DivideByZero);
+    }
+    let negative = x != T::ZERO
+        && y != T::ZERO
+        && z != T::ZERO
+        && ((x < T::ZERO) ^ (y < T::ZERO) ^ (z < T::ZERO) ^ (d1 < T::ZERO) ^ (d2 < T::ZERO));
+    let out_of_bounds = if negative {
+        FixedPointError::Underflow
+    } else {
+        FixedPointError::Overflow
+    };
+    let [x, y, z, d1, d2] = [x, y, z, d1, d2].map(magnitude);
+    let (x, y, z, d1, d2) = match (x, y, z, d1, d2) {
+        (Some(x), Some(y), Some(z), Some(d1), Some(d2)) => (x, y, z, d1, d2),
+        // every supported type has a magnitude that fits in a u128
+        _ => return Err(out_of_bounds),
+    };
+
+    // x * y * z / d1 = q1 * z + r1 * z / d1, where x * y = q1 * d1 + r1
+    let (q1, r1) = U256::mul_u128(x, y)
+        .div_rem_u128(d1)
+        .ok_or(FixedPointError::DivideByZero)?;
+    let (q2, r2) = U256::mul_u128(r1, z)
+        .div_rem_u128(d1)
+        .ok_or(FixedPointError::DivideByZero)?;
+    // q1 * z >= 2^256 means the result is at least 2^256 / d2, which overflows
+    let numerator = q1
+        .checked_mul_u128(z)
+        .and_then(|product| product.checked_add(q2))
+        .ok_or(out_of_bounds)?;
+
+    // the exact result is q3 + (r3 + r2 / d1) / d2 = q3 + (r3 * d1 + r2) / (d1 * d2)
+    let (q3, r3) = numerator
+        .div_rem_u128(d2)
+        .ok_or(FixedPointError::DivideByZero)?;
+    // r3 < d2 and r2 < d1, so r3 * d1 + r2 < d1 * d2 cannot overflow
+    let remainder = U256::mul_u128(r3, d1)
+        .checked_add_u128(r2)
+        .ok_or(out_of_bounds)?;
+    let divisor = U256::mul_u128(d1, d2);
+    let mut quotient = q3.to_u128().ok_or(out_of_bounds)?;
+    if rounding.round_away_from_zero(
+        negative,
+        quotient % 2 == 1,
+        Remainder::from_u256(remainder, divisor),
+    ) {
+        quotient = quotient.checked_add(1).ok_or(out_of_bounds)?;
+    }
+    from_magnitude(negative, quotient).ok_or(out_of_bounds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mul_mul_div_rounds_once() {
+        // 1.2345678 * 3.1415926 * 0.9876543 = 3.83062615...
+        let x: i128 = 1_2345678;
+        let y: i128 = 3_1415926;
+        let z: i128 = 9876543;
+
+        let floor = mul_mul_div(x, y, z, 1_0000000, 1_0000000, Rounding::Floor).unwrap();
+        let ceil = mul_mul_div(x, y, z, 1_0000000, 1_0000000, Rounding::Ceil).unwrap();
+
+        assert_eq!(floor, 3_8306261);
+        assert_eq!(ceil, 3_8306262);
+    }
+
+    #[test]
+    fn test_mul_mul_div_exact() {
+        assert_eq!(mul_mul_div(6_u64, 10, 15, 4, 5, Rounding::Ceil), Ok(45));
+        assert_eq!(mul_mul_div(0_i128, -10, 15, 4, 5, Rounding::Floor), Ok(0));
+    }
+
+    #[test]
+    fn test_mul_mul_div_negative() {
+        // -7 * 3 * 5 / (2 * 4) = -13.125
+        assert_eq!(mul_mul_div(-7_i32, 3, 5, 2, 4, Rounding::Floor), Ok(-14));
+        assert_eq!(mul_mul_div(-7_i32, 3, 5, 2, 4, Rounding::Ceil), Ok(-13));
+        assert_eq!(mul_mul_div(7_i32, 3, 5, -2, -4, Rounding::Floor), Ok(13));
+        assert_eq!(mul_mul_div(7_i32, 3, 5, 2, -4, Rounding::HalfUp), Ok(-13));
+    }
+
+    #[test]
+    fn test_mul_mul_div_half() {
+        // 3 * 5 * 7 / (6 * 7) = 2.5
+        assert_eq!(mul_mul_div(3_u64, 5, 7, 6, 7, Rounding::HalfUp), Ok(3));
+        assert_eq!(mul_mul_div(3_u64, 5, 7, 6, 7, Rounding::HalfEven), Ok(2));
+        // 1 * 1 * 1 / (3 * 3) = 0.111...
+        assert_eq!(mul_mul_div(1_u64, 1, 1, 3, 3, Rounding::HalfUp), Ok(0));
+    }
+
+    #[test]
+    fn test_mul_mul_div_phantom_overflow() {
+        let max = i128::MAX;
+
+        assert_eq!(
+            mul_mul_div(max, max, max, max, max, Rounding::Floor),
+            Ok(max)
+        );
+        assert_eq!(
+            mul_mul_div(
+                u128::MAX,
+                u128::MAX,
+                u128::MAX - 1,
+                u128::MAX,
+                u128::MAX,
+                Rounding::Ceil
+            ),
+            Ok(u128::MAX - 1)
+        );
+        // u128::MAX * u128::MAX * 3 / (3 * u128::MAX)
+        assert_eq!(
+            mul_mul_div(u128::MAX, u128::MAX, 3, 3, u128::MAX, Rounding::Floor),
+            Ok(u128::MAX)
+        );
+        assert_eq!(
+            mul_mul_div(u64::MAX, u64::MAX, 7, u64::MAX, 7, Rounding::Ceil),
+            Ok(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_mul_mul_div_large_remainder() {
+        // the remainders of both divisions are close to u128::MAX
+        let d = u128::MAX - 1;
+
+        assert_eq!(
+            mul_mul_div(u128::MAX, 1, u128::MAX, d, d, Rounding::Floor),
+            Ok(1)
+        );
+        assert_eq!(
+            mul_mul_div(u128::MAX, 1, u128::MAX, d, d, Rounding::Ceil),
+            Ok(2)
+        );
+        assert_eq!(
+            mul_mul_div(u128::MAX, 1, u128::MAX, d, d, Rounding::HalfUp),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn test_mul_mul_div_errors() {
+        assert_eq!(
+            mul_mul_div(1_i128, 1, 1, 0, 1, Rounding::Floor),
+            Err(FixedPointError::DivideByZero)
+        );
+        assert_eq!(
+            mul_mul_div(1_i128, 1, 1, 1, 0, Rounding::Floor),
+            Err(FixedPointError::DivideByZero)
+        );
+        assert_eq!(
+            mul_mul_div(u128::MAX, u128::MAX, u128::MAX, 1, 2, Rounding::Floor),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!(
+            mul_mul_div(i128::MIN, 2, 1, 1, 2, Rounding::Floor),
+            Ok(i128::MIN)
+        );
+        assert_eq!(
+            mul_mul_div(i128::MIN, 2, 1, 1, 1, Rounding::Floor),
+            Err(FixedPointError::Underflow)
+        );
+        assert_eq!(
+            mul_mul_div(u64::MAX, 2, 1, 1, 1, Rounding::Floor),
+            Err(FixedPointError::Overflow)
+        );
+    }
+}
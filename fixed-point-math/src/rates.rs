@@ -0,0 +1,689 @@
+//! Kinked utilization interest rate model for lending markets
+//!
+//! The borrow rate increases linearly with `slope_1` until the optimal utilization,
+//! and with the steeper `slope_2` after it. Rates are annual, and every value uses the
+//! scale of the model. Results are rounded in favor of the protocol: utilization and
+//! borrow rates are rounded up, and supply rates are rounded down.
+//!
+//! The module also contains helpers to grow a cumulative interest index over elapsed
+//! time, and to convert between APR and APY.
+
+#[allow(deprecated)]
+use crate::{
+    error::FixedPointError, exp_log, fixed_point::FixedPoint, integer::Integer, rounding::Rounding,
+};
+
+/// The number of seconds in a 365 day year
+pub const SECONDS_PER_YEAR: i128 = 31_536_000;
+
+/// A piecewise-linear interest rate model with a single kink at the optimal utilization
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KinkedRateModel<T> {
+    /// The value representing 1, or 100%
+    pub scale: T,
+    /// The borrow rate at 0% utilization
+    pub base_rate: T,
+    /// The increase of the borrow rate from 0% to the optimal utilization
+    pub slope_1: T,
+    /// The increase of the borrow rate from the optimal utilization to 100%
+    pub slope_2: T,
+    /// The utilization the rate model targets, where the slope changes
+    pub optimal_utilization: T,
+}
+
+#[allow(deprecated)]
+impl<T: FixedPoint + Integer> KinkedRateModel<T> {
+    /// Safely calculates the utilization, as total_borrowed / total_supplied rounded up
+    /// and capped at 100%. An empty market has a utilization of 0.
+    ///
+    /// Returns an error if any value is negative or if the result does not fit in `T`.
+    pub fn try_utilization(
+        &self,
+        total_borrowed: T,
+        total_supplied: T,
+    ) -> Result<T, FixedPointError> {
+        self.check()?;
+        if total_borrowed < T::ZERO || total_supplied < T::ZERO {
+            return Err(FixedPointError::InvalidInput);
+        }
+        if total_borrowed == T::ZERO {
+            return Ok(T::ZERO);
+        }
+        if total_borrowed >= total_supplied {
+            return Ok(self.scale);
+        }
+        total_borrowed.try_fixed_div_ceil(total_supplied, self.scale)
+    }
+
+    /// Safely calculates the annual borrow rate at `utilization`, rounded up.
+    ///
+    /// Returns an error if the model or the utilization are invalid, or if the result
+    /// does not fit in `T`.
+    pub fn try_borrow_rate(&self, utilization: T) -> Result<T, FixedPointError> {
+        self.check_utilization(utilization)?;
+        if utilization <= self.optimal_utilization {
+            if utilization == T::ZERO {
+                return Ok(self.base_rate);
+            }
+            let increase = self
+                .slope_1
+                .try_fixed_mul_ceil(utilization, self.optimal_utilization)?;
+            return self.checked_add(self.base_rate, increase);
+        }
+        // rate = base + slope_1 + slope_2 * (u - optimal) / (1 - optimal)
+        let increase = self.slope_2.try_fixed_mul_ceil(
+            utilization - self.optimal_utilization,
+            self.scale - self.optimal_utilization,
+        )?;
+        let rate = self.checked_add(self.base_rate, self.slope_1)?;
+        self.checked_add(rate, increase)
+    }
+
+    /// Safely calculates the annual supply rate at `utilization`, as the borrow rate
+    /// paid on the utilized share of the supply, net of the `reserve_factor` taken by
+    /// the protocol. Rounded down.
+    ///
+    /// Returns an error if the model, the utilization or the reserve factor are invalid,
+    /// or if the result does not fit in `T`.
+    pub fn try_supply_rate(&self, utilization: T, reserve_factor: T) -> Result<T, FixedPointError> {
+        if reserve_factor < T::ZERO || reserve_factor > self.scale {
+            return Err(FixedPointError::InvalidInput);
+        }
+        let borrow_rate = self.try_borrow_rate(utilization)?;
+        borrow_rate
+            .try_fixed_mul_floor(utilization, self.scale)?
+            .try_fixed_mul_floor(self.scale - reserve_factor, self.scale)
+    }
+
+    /// Safely calculates the utilization, as total_borrowed / total_supplied rounded up
+    /// and capped at 100%. Returns None if any value is negative or if the result
+    /// overflows.
+    pub fn utilization(&self, total_borrowed: T, total_supplied: T) -> Option<T> {
+        self.try_utilization(total_borrowed, total_supplied).ok()
+    }
+
+    /// Safely calculates the annual borrow rate at `utilization`, rounded up. Returns
+    /// None if the model or the utilization are invalid, or if the result overflows.
+    pub fn borrow_rate(&self, utilization: T) -> Option<T> {
+        self.try_borrow_rate(utilization).ok()
+    }
+
+    /// Safely calculates the annual supply rate at `utilization`, net of the
+    /// `reserve_factor`, rounded down. Returns None if the model, the utilization or
+    /// the reserve factor are invalid, or if the result overflows.
+    pub fn supply_rate(&self, utilization: T, reserve_factor: T) -> Option<T> {
+        self.try_supply_rate(utilization, reserve_factor).ok()
+    }
+
+    /// Validates that every parameter is non-negative, and that the optimal
+    /// utilization is within (0%, 100%]
+    fn check(&self) -> Result<(), FixedPointError> {
+        if self.scale <= T::ZERO
+            || self.base_rate < T::ZERO
+            || self.slope_1 < T::ZERO
+            || self.slope_2 < T::ZERO
+            || self.optimal_utilization <= T::ZERO
+            || self.optimal_utilization > self.scale
+        {
+            return Err(FixedPointError::InvalidInput);
+        }
+        Ok(())
+    }
+
+    fn check_utilization(&self, utilization: T) -> Result<(), FixedPointError> {
+        self.check()?;
+        if utilization < T::ZERO || utilization > self.scale {
+            return Err(FixedPointError::InvalidInput);
+        }
+        Ok(())
+    }
+
+    fn checked_add(&self, x: T, y: T) -> Result<T, FixedPointError> {
+        x.checked_add(y).ok_or(FixedPointError::Overflow)
+    }
+}
+
+/// Safely calculates the interest accrued on `principal` over `elapsed` seconds at
+/// the annual `rate`, using simple interest. `principal` can have any scale, and the
+/// rate uses `scale`. The result is rounded once in the direction of `rounding`.
+///
+/// Returns an error if the result does not fit in `T`, or if the scale is 0.
+#[allow(deprecated)]
+pub fn try_accrue_interest<T: FixedPoint + Integer>(
+    principal: T,
+    rate: T,
+    elapsed: T,
+    scale: T,
+    rounding: Rounding,
+) -> Result<T, FixedPointError> {
+    let seconds_per_year = T::from_i128(SECONDS_PER_YEAR).ok_or(FixedPointError::Overflow)?;
+    principal.try_mul_mul_div(rate, elapsed, scale, seconds_per_year, rounding)
+}
+
+/// Safely calculates the interest accrued on `principal` over `elapsed` seconds at
+/// the annual `rate`, using simple interest. `principal` can have any scale, and the
+/// rate uses `scale`. The result is rounded in the direction of `rounding`.
+///
+/// Returns None if the result overflows, or if the scale is 0.
+#[allow(deprecated)]
+pub fn accrue_interest<T: FixedPoint + Integer>(
+    principal: T,
+    rate: T,
+    elapsed: T,
+    scale: T,
+    rounding: Rounding,
+) -> Option<T> {
+    try_accrue_interest(principal, rate, elapsed, scale, rounding).ok()
+}
+
+/// Safely converts the annual `rate` to a rate per second, rounded in the direction of
+/// `rounding`. Returns an error if the seconds per year do not fit in `T`.
+#[allow(deprecated)]
+pub fn try_rate_per_second<T: FixedPoint + Integer>(
+    rate: T,
+    rounding: Rounding,
+) -> Result<T, FixedPointError> {
+    let seconds_per_year = T::from_i128(SECONDS_PER_YEAR).ok_or(FixedPointError::Overflow)?;
+    rate.try_fixed_mul(T::ONE, seconds_per_year, rounding)
+}
+
+/// Safely converts the annual `rate` to a rate per second, rounded in the direction of
+/// `rounding`. Returns None if the seconds per year do not fit in `T`.
+#[allow(deprecated)]
+pub fn rate_per_second<T: FixedPoint + Integer>(rate: T, rounding: Rounding) -> Option<T> {
+    try_rate_per_second(rate, rounding).ok()
+}
+
+/// Safely grows `index` by the simple interest of `rate` per period over `elapsed`
+/// periods, as index * (1 + rate * elapsed). `index` can have any scale, and the rate
+/// uses `scale`. The result is rounded in the direction of `rounding`.
+///
+/// Returns an error if the rate or elapsed periods are negative, if the result does not
+/// fit in `T`, or if the scale is 0.
+#[allow(deprecated)]
+pub fn try_accrue_index_linear<T: FixedPoint + Integer>(
+    index: T,
+    rate: T,
+    elapsed: T,
+    scale: T,
+    rounding: Rounding,
+) -> Result<T, FixedPointError> {
+    check_index_inputs(rate, elapsed)?;
+    let growth = rate
+        .checked_mul(elapsed)
+        .and_then(|interest| scale.checked_add(interest))
+        .ok_or(FixedPointError::Overflow)?;
+    index.try_fixed_mul(growth, scale, rounding)
+}
+
+/// Safely grows `index` by the simple interest of `rate` per period over `elapsed`
+/// periods, as index * (1 + rate * elapsed). `index` can have any scale, and the rate
+/// uses `scale`. The result is rounded in the direction of `rounding`.
+///
+/// Returns None if the rate or elapsed periods are negative, if the result overflows,
+/// or if the scale is 0.
+#[allow(deprecated)]
+pub fn accrue_index_linear<T: FixedPoint + Integer>(
+    index: T,
+    rate: T,
+    elapsed: T,
+    scale: T,
+    rounding: Rounding,
+) -> Option<T> {
+    try_accrue_index_linear(index, rate, elapsed, scale, rounding).ok()
+}
+
+/// Safely grows `index` by `rate` per period, compounded over `elapsed` periods, as
+/// index * (1 + rate)^elapsed. `index` can have any scale, and the rate uses `scale`.
+/// The result is rounded in the direction of `rounding`.
+///
+/// (1 + rate)^elapsed is approximated by the first four terms of its binomial (Taylor)
+/// expansion, 1 + r*t + r^2*t*(t-1)/2 + r^3*t*(t-1)*(t-2)/6, which always underestimates
+/// the growth. The relative error is below (r*t)^4 / 24 * e^(r*t), which is about
+/// 2.3e-12 for a day at 100% APR, 2.7e-7 for a year at 5% APR, and 8.1e-5 for a year at
+/// 20% APR. Accrue frequently, or use `fixed_pow`, when r*t can be large.
+///
+/// Returns an error if the rate or elapsed periods are negative, if the result does not
+/// fit in `T`, or if the scale is 0.
+#[allow(deprecated)]
+pub fn try_accrue_index_compound<T: FixedPoint + Integer>(
+    index: T,
+    rate: T,
+    elapsed: T,
+    scale: T,
+    rounding: Rounding,
+) -> Result<T, FixedPointError> {
+    check_index_inputs(rate, elapsed)?;
+    let two = T::ONE + T::ONE;
+    let three = two + T::ONE;
+    let first = rate.checked_mul(elapsed).ok_or(FixedPointError::Overflow)?;
+    let mut growth = scale.checked_add(first).ok_or(FixedPointError::Overflow)?;
+    if elapsed > T::ONE {
+        // each term is the previous term * r * (t - k) / (k + 1), computed from r * t to
+        // keep the precision of the scale
+        let second_denominator = scale.checked_mul(two).ok_or(FixedPointError::Overflow)?;
+        let second_factor = rate
+            .checked_mul(elapsed - T::ONE)
+            .ok_or(FixedPointError::Overflow)?;
+        let second = first.try_fixed_mul(second_factor, second_denominator, rounding)?;
+        growth = growth
+            .checked_add(second)
+            .ok_or(FixedPointError::Overflow)?;
+        if elapsed > two {
+            let third_denominator = scale.checked_mul(three).ok_or(FixedPointError::Overflow)?;
+            let third_factor = rate
+                .checked_mul(elapsed - two)
+                .ok_or(FixedPointError::Overflow)?;
+            let third = second.try_fixed_mul(third_factor, third_denominator, rounding)?;
+            growth = growth.checked_add(third).ok_or(FixedPointError::Overflow)?;
+        }
+    }
+    index.try_fixed_mul(growth, scale, rounding)
+}
+
+/// Safely grows `index` by `rate` per period, compounded over `elapsed` periods, as
+/// index * (1 + rate)^elapsed. See `try_accrue_index_compound` for the error bound of
+/// the approximation.
+///
+/// Returns None if the rate or elapsed periods are negative, if the result overflows,
+/// or if the scale is 0.
+#[allow(deprecated)]
+pub fn accrue_index_compound<T: FixedPoint + Integer>(
+    index: T,
+    rate: T,
+    elapsed: T,
+    scale: T,
+    rounding: Rounding,
+) -> Option<T> {
+    try_accrue_index_compound(index, rate, elapsed, scale, rounding).ok()
+}
+
+/// Safely converts `apr` to the APY it earns when compounded `periods_per_year` times,
+/// as (1 + apr / periods_per_year)^periods_per_year - 1. Use `SECONDS_PER_YEAR` for a
+/// per-second rate, or the number of ledgers per year for a per-ledger rate. Both
+/// rates use `scale`, and the result is rounded to the nearest value.
+///
+/// The result is computed with 36 decimals of intermediate precision, so converting it
+/// back with `apy_to_apr` returns `apr` within a few units.
+///
+/// Returns an error if `periods_per_year` is not positive, if `apr` is not greater than
+/// -100% per period, if the result does not fit in `T`, or if the scale is 0.
+#[allow(deprecated)]
+pub fn try_apr_to_apy<T: FixedPoint + Integer>(
+    apr: T,
+    periods_per_year: T,
+    scale: T,
+) -> Result<T, FixedPointError> {
+    let apy = exp_log::apr_to_apy(
+        apr.to_i128().ok_or(FixedPointError::Overflow)?,
+        periods_per_year
+            .to_i128()
+            .ok_or(FixedPointError::Overflow)?,
+        scale.to_i128().ok_or(FixedPointError::Overflow)?,
+    )?;
+    T::from_i128(apy).ok_or(FixedPointError::Overflow)
+}
+
+/// Safely converts `apr` to the APY it earns when compounded `periods_per_year` times.
+/// See `try_apr_to_apy` for details.
+///
+/// Returns None if `periods_per_year` is not positive, if `apr` is not greater than
+/// -100% per period, if the result overflows, or if the scale is 0.
+#[allow(deprecated)]
+pub fn apr_to_apy<T: FixedPoint + Integer>(apr: T, periods_per_year: T, scale: T) -> Option<T> {
+    try_apr_to_apy(apr, periods_per_year, scale).ok()
+}
+
+/// Safely converts `apy` to the APR that earns it when compounded `periods_per_year`
+/// times, as periods_per_year * ((1 + apy)^(1 / periods_per_year) - 1). Both rates use
+/// `scale`, and the result is rounded to the nearest value.
+///
+/// This is the inverse of `apr_to_apy`, and a round trip returns the original rate
+/// within a few units.
+///
+/// Returns an error if `periods_per_year` is not positive, if `apy` is not greater than
+/// -100%, if the result does not fit in `T`, or if the scale is 0.
+#[allow(deprecated)]
+pub fn try_apy_to_apr<T: FixedPoint + Integer>(
+    apy: T,
+    periods_per_year: T,
+    scale: T,
+) -> Result<T, FixedPointError> {
+    let apr = exp_log::apy_to_apr(
+        apy.to_i128().ok_or(FixedPointError::Overflow)?,
+        periods_per_year
+            .to_i128()
+            .ok_or(FixedPointError::Overflow)?,
+        scale.to_i128().ok_or(FixedPointError::Overflow)?,
+    )?;
+    T::from_i128(apr).ok_or(FixedPointError::Overflow)
+}
+
+/// Safely converts `apy` to the APR that earns it when compounded `periods_per_year`
+/// times. See `try_apy_to_apr` for details.
+///
+/// Returns None if `periods_per_year` is not positive, if `apy` is not greater than
+/// -100%, if the result overflows, or if the scale is 0.
+#[allow(deprecated)]
+pub fn apy_to_apr<T: FixedPoint + Integer>(apy: T, periods_per_year: T, scale: T) -> Option<T> {
+    try_apy_to_apr(apy, periods_per_year, scale).ok()
+}
+
+fn check_index_inputs<T: Integer>(rate: T, elapsed: T) -> Result<(), FixedPointError> {
+    if rate < T::ZERO || elapsed < T::ZERO {
+        return Err(FixedPointError::InvalidInput);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODEL: KinkedRateModel<i128> = KinkedRateModel {
+        scale: 1_0000000,
+        base_rate: 200000,
+        slope_1: 400000,
+        slope_2: 7500000,
+        optimal_utilization: 8000000,
+    };
+
+    #[test]
+    fn test_utilization() {
+        assert_eq!(MODEL.utilization(1_0000000, 3_0000000), Some(3333334));
+        assert_eq!(MODEL.utilization(0, 0), Some(0));
+        assert_eq!(MODEL.utilization(0, 3_0000000), Some(0));
+        assert_eq!(MODEL.utilization(3_0000001, 3_0000000), Some(1_0000000));
+        assert_eq!(
+            MODEL.try_utilization(-1, 3_0000000),
+            Err(FixedPointError::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn test_borrow_rate() {
+        assert_eq!(MODEL.borrow_rate(0), Some(200000));
+        // 2% + 4% * 0.5 / 0.8
+        assert_eq!(MODEL.borrow_rate(5000000), Some(450000));
+        assert_eq!(MODEL.borrow_rate(8000000), Some(600000));
+        // 2% + 4% + 75% * 0.1 / 0.2
+        assert_eq!(MODEL.borrow_rate(9000000), Some(4350000));
+        assert_eq!(MODEL.borrow_rate(1_0000000), Some(8100000));
+        // 4% * 0.0000001 / 0.8 is rounded up
+        assert_eq!(MODEL.borrow_rate(1), Some(200001));
+    }
+
+    #[test]
+    fn test_supply_rate() {
+        // 43.5% * 0.9 * (1 - 0.1)
+        assert_eq!(MODEL.supply_rate(9000000, 1000000), Some(3523500));
+        assert_eq!(MODEL.supply_rate(0, 1000000), Some(0));
+        assert_eq!(MODEL.supply_rate(1_0000000, 1_0000000), Some(0));
+        // 2.0000001% * 0.0000001 is rounded down
+        assert_eq!(MODEL.supply_rate(1, 0), Some(0));
+    }
+
+    #[test]
+    fn test_rates_at_other_scales() {
+        let model: KinkedRateModel<u64> = KinkedRateModel {
+            scale: 1_000_000_000_000_000_000,
+            base_rate: 0,
+            slope_1: 40_000_000_000_000_000,
+            slope_2: 3_000_000_000_000_000_000,
+            optimal_utilization: 900_000_000_000_000_000,
+        };
+
+        let utilization = model.utilization(95, 100).unwrap();
+
+        assert_eq!(utilization, 950_000_000_000_000_000);
+        assert_eq!(
+            model.borrow_rate(utilization),
+            Some(1_540_000_000_000_000_000)
+        );
+        assert_eq!(
+            model.supply_rate(utilization, 0),
+            Some(1_463_000_000_000_000_000)
+        );
+    }
+
+    #[test]
+    fn test_invalid_model() {
+        let model = KinkedRateModel {
+            optimal_utilization: 0,
+            ..MODEL
+        };
+        assert_eq!(model.try_borrow_rate(0), Err(FixedPointError::InvalidInput));
+
+        let model = KinkedRateModel {
+            optimal_utilization: 1_0000001,
+            ..MODEL
+        };
+        assert_eq!(model.try_borrow_rate(0), Err(FixedPointError::InvalidInput));
+
+        let model = KinkedRateModel {
+            slope_2: -1,
+            ..MODEL
+        };
+        assert_eq!(model.try_borrow_rate(0), Err(FixedPointError::InvalidInput));
+
+        assert_eq!(
+            MODEL.try_borrow_rate(1_0000001),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            MODEL.try_supply_rate(5000000, 1_0000001),
+            Err(FixedPointError::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn test_full_optimal_utilization() {
+        let model = KinkedRateModel {
+            optimal_utilization: 1_0000000,
+            ..MODEL
+        };
+
+        assert_eq!(model.borrow_rate(1_0000000), Some(600000));
+    }
+
+    #[test]
+    fn test_accrue_interest() {
+        let principal: i128 = 1000_0000000;
+        let rate: i128 = 500000;
+
+        // 1000 * 5% over a year
+        assert_eq!(
+            accrue_interest(
+                principal,
+                rate,
+                SECONDS_PER_YEAR,
+                1_0000000,
+                Rounding::Floor
+            ),
+            Some(50_0000000)
+        );
+        // 1000 * 5% * 3600 / 31536000 = 0.005707762557...
+        assert_eq!(
+            accrue_interest(principal, rate, 3600, 1_0000000, Rounding::Floor),
+            Some(57077)
+        );
+        assert_eq!(
+            accrue_interest(principal, rate, 3600, 1_0000000, Rounding::Ceil),
+            Some(57078)
+        );
+        // 0.0000003 * 50% over two thirds of a year is exactly 0.0000001
+        assert_eq!(
+            accrue_interest(3, 5000000, 21024000, 1_0000000, Rounding::Floor),
+            Some(1)
+        );
+        assert_eq!(
+            try_accrue_interest(
+                i128::MAX,
+                rate,
+                SECONDS_PER_YEAR * 100,
+                1_0000000,
+                Rounding::Floor
+            ),
+            Err(FixedPointError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_rate_per_second() {
+        let rate: u64 = 50_000_000_000_000_000;
+
+        // 5% / 31536000 = 0.000000001585489599...
+        assert_eq!(rate_per_second(rate, Rounding::Floor), Some(1_585_489_599));
+        assert_eq!(rate_per_second(rate, Rounding::Ceil), Some(1_585_489_600));
+        assert_eq!(rate_per_second(500000_i32, Rounding::Ceil), Some(1));
+    }
+
+    #[test]
+    fn test_accrue_index_linear() {
+        let scale: i128 = 1_000_000_000_000_000_000;
+        let rate = rate_per_second(50_000_000_000_000_000, Rounding::Floor).unwrap();
+
+        assert_eq!(
+            accrue_index_linear(scale, rate, SECONDS_PER_YEAR, scale, Rounding::Floor),
+            Some(1_049_999_999_994_064_000)
+        );
+        assert_eq!(
+            accrue_index_linear(1_2000000_u64, 200000, 3, 1_0000000, Rounding::Ceil),
+            Some(1_2720000)
+        );
+        assert_eq!(
+            accrue_index_linear(scale, rate, 0, scale, Rounding::Floor),
+            Some(scale)
+        );
+    }
+
+    #[test]
+    fn test_accrue_index_compound() {
+        let scale: i128 = 1_000_000_000_000_000_000;
+        let rate = rate_per_second(50_000_000_000_000_000, Rounding::Floor).unwrap();
+
+        // e^0.05 = 1.051271096..., and the expansion error is below 2.7e-7
+        assert_eq!(
+            accrue_index_compound(scale, rate, SECONDS_PER_YEAR, scale, Rounding::Floor),
+            Some(1_051_270_833_285_474_011)
+        );
+        assert_eq!(
+            accrue_index_compound(scale, rate, SECONDS_PER_YEAR, scale, Rounding::Ceil),
+            Some(1_051_270_833_285_474_013)
+        );
+        // (1 + r)^86400 = 1.000136995684296814..., within 2e-17 over a day
+        assert_eq!(
+            accrue_index_compound(scale, rate, 86400, scale, Rounding::Floor),
+            Some(1_000_136_995_684_296_799)
+        );
+    }
+
+    #[test]
+    fn test_accrue_index_compound_few_periods() {
+        let scale: i128 = 1_000_000_000_000_000_000;
+        let rate: i128 = 1_585_489_599;
+
+        // the expansion is exact for up to 3 periods
+        let expected = [
+            1_000_000_000_000_000_000,
+            1_000_000_001_585_489_599,
+            1_000_000_003_170_979_200,
+            1_000_000_004_756_468_804,
+        ];
+        for (elapsed, expected) in expected.into_iter().enumerate() {
+            assert_eq!(
+                accrue_index_compound(scale, rate, elapsed as i128, scale, Rounding::Floor),
+                Some(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn test_accrue_index_errors() {
+        assert_eq!(
+            try_accrue_index_linear(1_0000000_i128, -1, 10, 1_0000000, Rounding::Floor),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_accrue_index_compound(1_0000000_i128, 1, -10, 1_0000000, Rounding::Floor),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_accrue_index_compound(1_0000000_i128, 1, 10, 0, Rounding::Floor),
+            Err(FixedPointError::DivideByZero)
+        );
+        assert_eq!(
+            try_accrue_index_compound(u64::MAX, 1, 10, 1_0000000, Rounding::Floor),
+            Err(FixedPointError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_apr_to_apy() {
+        let scale: i128 = 1_000_000_000_000_000_000;
+
+        // (1 + 0.05 / 31536000)^31536000 - 1 = 0.051271096334354555...
+        assert_eq!(
+            apr_to_apy(50_000_000_000_000_000, SECONDS_PER_YEAR, scale),
+            Some(51_271_096_334_354_555)
+        );
+        // (1 + 0.12 / 12)^12 - 1 = 0.126825030...
+        assert_eq!(apr_to_apy(1200000_u64, 12, 1_0000000), Some(1268250));
+        assert_eq!(apr_to_apy(1200000_i128, 1, 1_0000000), Some(1200000));
+        assert_eq!(apr_to_apy(0_i128, SECONDS_PER_YEAR, 1_0000000), Some(0));
+    }
+
+    #[test]
+    fn test_apy_to_apr() {
+        let scale: i128 = 1_000_000_000_000_000_000;
+
+        // 31536000 * (1.1^(1 / 31536000) - 1) = 0.095310179948351216...
+        let apr = apy_to_apr(100_000_000_000_000_000, SECONDS_PER_YEAR, scale).unwrap();
+        assert!((apr - 95_310_179_948_351_217).abs() <= 1);
+        assert_eq!(apy_to_apr(1268250_u64, 12, 1_0000000), Some(1200000));
+        assert_eq!(apy_to_apr(1200000_i128, 1, 1_0000000), Some(1200000));
+    }
+
+    #[test]
+    fn test_apr_apy_round_trip() {
+        let scale: i128 = 1_000_000_000_000_000_000;
+
+        for apr in [
+            1,
+            12_345,
+            10_000_000_000_000_000,
+            250_000_000_000_000_000,
+            scale * 3,
+        ] {
+            for periods in [1, 12, 365, SECONDS_PER_YEAR] {
+                let apy = apr_to_apy(apr, periods, scale).unwrap();
+                let result = apy_to_apr(apy, periods, scale).unwrap();
+                assert!((result - apr).abs() <= 2, "{} {} {}", apr, periods, result);
+            }
+        }
+    }
+
+    #[test]
+    fn test_apr_apy_errors() {
+        assert_eq!(
+            try_apr_to_apy(1_0000000_i128, 0, 1_0000000),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_apr_to_apy(-12_0000000_i128, 12, 1_0000000),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_apy_to_apr(-1_0000000_i128, 12, 1_0000000),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_apr_to_apy(1_0000000_i128, 12, 0),
+            Err(FixedPointError::DivideByZero)
+        );
+        assert_eq!(
+            try_apr_to_apy(42_0000000_u32, 12, 1_0000000),
+            Err(FixedPointError::Overflow)
+        );
+    }
+}
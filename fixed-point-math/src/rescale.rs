@@ -0,0 +1,106 @@
+use crate::{
+    error::FixedPointError,
+    integer::{from_magnitude, magnitude, Integer},
+    rounding::{Remainder, Rounding},
+};
+
+/// Converts x from `from_decimals` to `to_decimals` decimal places, rounding in the
+/// direction of `rounding` when decimal places are dropped
+pub(crate) fn rescale<T: Integer>(
+    x: T,
+    from_decimals: u32,
+    to_decimals: u32,
+    rounding: Rounding,
+) -> Result<T, FixedPointError> {
+    let negative = x < T::ZERO;
+    let out_of_bounds = if negative {
+        FixedPointError::Underflow
+    } else {
+        FixedPointError::Overflow
+    };
+    if from_decimals <= to_decimals {
+        if x == T::ZERO {
+            return Ok(x);
+        }
+        return T::TEN
+            .checked_pow(to_decimals - from_decimals)
+            .and_then(|factor| x.checked_mul(factor))
+            .ok_or(out_of_bounds);
+    }
+
+    // divide the magnitude as a u128, so the divisor can exceed the range of T
+    let magnitude = magnitude(x).ok_or(out_of_bounds)?;
+    let (mut quotient, remainder) = match 10_u128.checked_pow(from_decimals - to_decimals) {
+        Some(divisor) => (
+            magnitude / divisor,
+            Remainder::new(magnitude % divisor, divisor),
+        ),
+        // 10^39 is more than twice any u128, so the remainder is below half
+        None if magnitude == 0 => (0, Remainder::Zero),
+        None => (0, Remainder::BelowHalf),
+    };
+    if rounding.round_away_from_zero(negative, quotient % 2 == 1, remainder) {
+        quotient += 1;
+    }
+    from_magnitude(negative, quotient).ok_or(out_of_bounds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rescale_up() {
+        assert_eq!(
+            rescale(1_5000000_i128, 7, 18, Rounding::Floor),
+            Ok(1_500_000_000_000_000_000)
+        );
+        assert_eq!(
+            rescale(-1_5000000_i64, 7, 9, Rounding::Floor),
+            Ok(-1_500_000_000)
+        );
+        assert_eq!(rescale(0_u32, 0, 40, Rounding::Floor), Ok(0));
+        assert_eq!(rescale(42_u64, 7, 7, Rounding::Ceil), Ok(42));
+    }
+
+    #[test]
+    fn test_rescale_up_overflow() {
+        assert_eq!(
+            rescale(1_u32, 0, 10, Rounding::Floor),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!(
+            rescale(-1_i128, 0, 39, Rounding::Floor),
+            Err(FixedPointError::Underflow)
+        );
+        assert_eq!(
+            rescale(i64::MAX, 7, 8, Rounding::Floor),
+            Err(FixedPointError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_rescale_down_rounds() {
+        let x: i128 = 1_234_567_890_123_456_789;
+
+        assert_eq!(rescale(x, 18, 7, Rounding::Floor), Ok(1_2345678));
+        assert_eq!(rescale(x, 18, 7, Rounding::Ceil), Ok(1_2345679));
+        assert_eq!(rescale(-x, 18, 7, Rounding::Floor), Ok(-1_2345679));
+        assert_eq!(rescale(-x, 18, 7, Rounding::Ceil), Ok(-1_2345678));
+        assert_eq!(rescale(-x, 18, 7, Rounding::HalfUp), Ok(-1_2345679));
+        assert_eq!(rescale(25_u64, 1, 0, Rounding::HalfEven), Ok(2));
+        assert_eq!(rescale(35_u64, 1, 0, Rounding::HalfEven), Ok(4));
+    }
+
+    #[test]
+    fn test_rescale_down_large_divisor() {
+        // 10^19 does not fit in an i64, but the result is still rounded correctly
+        assert_eq!(rescale(i64::MAX, 19, 0, Rounding::HalfUp), Ok(1));
+        assert_eq!(rescale(i64::MIN, 19, 0, Rounding::Floor), Ok(-1));
+        assert_eq!(rescale(i64::MIN, 19, 0, Rounding::TowardZero), Ok(0));
+        assert_eq!(rescale(u128::MAX, 50, 0, Rounding::Ceil), Ok(1));
+        assert_eq!(rescale(u128::MAX, 50, 0, Rounding::HalfUp), Ok(0));
+        assert_eq!(rescale(i128::MIN, 40, 1, Rounding::Floor), Ok(-1));
+        assert_eq!(rescale(0_i128, 60, 0, Rounding::Ceil), Ok(0));
+    }
+}
@@ -0,0 +1,173 @@
+use core::cmp::Ordering;
+
+use crate::{integer::Integer, u256::U256};
+
+/// The rounding direction applied to the result of a fixed-point operation
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Rounding {
+    /// Round towards negative infinity
+    Floor,
+    /// Round towards positive infinity
+    Ceil,
+    /// Round towards zero, truncating the result
+    TowardZero,
+    /// Round away from zero
+    AwayFromZero,
+    /// Round to the nearest value, with ties rounded away from zero
+    HalfUp,
+    /// Round to the nearest value, with ties rounded to the even value (banker's rounding)
+    HalfEven,
+}
+
+/// How the remainder of a truncated division compares to half of the divisor
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Remainder {
+    Zero,
+    BelowHalf,
+    Half,
+    AboveHalf,
+}
+
+impl Remainder {
+    /// Classifies the magnitude of a remainder against the magnitude of the divisor
+    /// it was produced by
+    pub(crate) fn new<T: Integer>(remainder: T, divisor: T) -> Remainder {
+        if remainder == T::ZERO {
+            return Remainder::Zero;
+        }
+        // remainder < divisor, so compare against the rest of the divisor to avoid an overflow
+        match remainder.cmp(&(divisor - remainder)) {
+            Ordering::Less => Remainder::BelowHalf,
+            Ordering::Equal => Remainder::Half,
+            Ordering::Greater => Remainder::AboveHalf,
+        }
+    }
+
+    /// Classifies a u128 remainder against the divisor it was produced by, usable in
+    /// `const` contexts
+    pub(crate) const fn from_u128(remainder: u128, divisor: u128) -> Remainder {
+        if remainder == 0 {
+            return Remainder::Zero;
+        }
+        let rest = divisor - remainder;
+        if remainder < rest {
+            Remainder::BelowHalf
+        } else if remainder == rest {
+            Remainder::Half
+        } else {
+            Remainder::AboveHalf
+        }
+    }
+
+    /// Classifies a 256-bit remainder against the divisor it was produced by
+    pub(crate) fn from_u256(remainder: U256, divisor: U256) -> Remainder {
+        if remainder.is_zero() {
+            return Remainder::Zero;
+        }
+        // remainder < divisor, so compare against the rest of the divisor to avoid an overflow
+        let rest = divisor.checked_sub(remainder).unwrap_or_default();
+        match remainder.cmp(&rest) {
+            Ordering::Less => Remainder::BelowHalf,
+            Ordering::Equal => Remainder::Half,
+            Ordering::Greater => Remainder::AboveHalf,
+        }
+    }
+}
+
+impl Rounding {
+    /// Returns true if a quotient truncated towards zero needs its magnitude
+    /// increased by one to be rounded in this direction
+    ///
+    /// ### Arguments
+    /// * `negative` - If the exact result is negative
+    /// * `odd` - If the truncated quotient is odd
+    /// * `remainder` - The remainder of the truncated division
+    pub(crate) const fn round_away_from_zero(
+        self,
+        negative: bool,
+        odd: bool,
+        remainder: Remainder,
+    ) -> bool {
+        // `matches!` instead of `==`, as `PartialEq` cannot be called in a const fn
+        if matches!(remainder, Remainder::Zero) {
+            return false;
+        }
+        match self {
+            Rounding::Floor => negative,
+            Rounding::Ceil => !negative,
+            Rounding::TowardZero => false,
+            Rounding::AwayFromZero => true,
+            Rounding::HalfUp => !matches!(remainder, Remainder::BelowHalf),
+            Rounding::HalfEven => {
+                matches!(remainder, Remainder::AboveHalf)
+                    || (matches!(remainder, Remainder::Half) && odd)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remainder_new() {
+        assert_eq!(Remainder::new(0_u64, 10), Remainder::Zero);
+        assert_eq!(Remainder::new(4_u64, 10), Remainder::BelowHalf);
+        assert_eq!(Remainder::new(5_u64, 10), Remainder::Half);
+        assert_eq!(Remainder::new(6_u64, 10), Remainder::AboveHalf);
+        assert_eq!(Remainder::new(5_u64, 11), Remainder::BelowHalf);
+        assert_eq!(
+            Remainder::new(u128::MAX - 1, u128::MAX),
+            Remainder::AboveHalf
+        );
+    }
+
+    #[test]
+    fn test_remainder_from_u128() {
+        assert_eq!(Remainder::from_u128(0, 10), Remainder::Zero);
+        assert_eq!(Remainder::from_u128(4, 10), Remainder::BelowHalf);
+        assert_eq!(Remainder::from_u128(5, 10), Remainder::Half);
+        assert_eq!(Remainder::from_u128(6, 10), Remainder::AboveHalf);
+        assert_eq!(
+            Remainder::from_u128(u128::MAX - 1, u128::MAX),
+            Remainder::AboveHalf
+        );
+    }
+
+    #[test]
+    fn test_round_away_from_zero_exact() {
+        for rounding in [
+            Rounding::Floor,
+            Rounding::Ceil,
+            Rounding::TowardZero,
+            Rounding::AwayFromZero,
+            Rounding::HalfUp,
+            Rounding::HalfEven,
+        ] {
+            assert!(!rounding.round_away_from_zero(false, true, Remainder::Zero));
+            assert!(!rounding.round_away_from_zero(true, true, Remainder::Zero));
+        }
+    }
+
+    #[test]
+    fn test_round_away_from_zero_directed() {
+        assert!(!Rounding::Floor.round_away_from_zero(false, false, Remainder::AboveHalf));
+        assert!(Rounding::Floor.round_away_from_zero(true, false, Remainder::BelowHalf));
+        assert!(Rounding::Ceil.round_away_from_zero(false, false, Remainder::BelowHalf));
+        assert!(!Rounding::Ceil.round_away_from_zero(true, false, Remainder::AboveHalf));
+        assert!(!Rounding::TowardZero.round_away_from_zero(true, false, Remainder::AboveHalf));
+        assert!(Rounding::AwayFromZero.round_away_from_zero(false, false, Remainder::BelowHalf));
+    }
+
+    #[test]
+    fn test_round_away_from_zero_nearest() {
+        assert!(!Rounding::HalfUp.round_away_from_zero(false, false, Remainder::BelowHalf));
+        assert!(Rounding::HalfUp.round_away_from_zero(true, false, Remainder::Half));
+        assert!(Rounding::HalfUp.round_away_from_zero(false, false, Remainder::AboveHalf));
+        assert!(!Rounding::HalfEven.round_away_from_zero(false, false, Remainder::BelowHalf));
+        assert!(!Rounding::HalfEven.round_away_from_zero(false, false, Remainder::Half));
+        assert!(Rounding::HalfEven.round_away_from_zero(true, true, Remainder::Half));
+        assert!(Rounding::HalfEven.round_away_from_zero(false, false, Remainder::AboveHalf));
+    }
+}
@@ -0,0 +1,151 @@
+use soroban_sdk::{panic_with_error, Env};
+
+#[allow(deprecated)]
+use crate::{fixed_point::FixedPoint, rounding::Rounding};
+
+/// Fixed-point operations that panic with a contract error on failure, such that the
+/// error code is preserved on-chain.
+///
+/// Only available with the `soroban` feature.
+#[allow(deprecated)]
+pub trait SorobanFixedPoint: FixedPoint {
+    /// Calculates x * y / denominator, rounded in the direction of `rounding`. Panics
+    /// with `error` if the result overflows or if the denominator is 0.
+    fn fixed_mul_or_panic<E: Into<soroban_sdk::Error>>(
+        self,
+        y: Self,
+        denominator: Self,
+        rounding: Rounding,
+        e: &Env,
+        error: E,
+    ) -> Self {
+        match self.try_fixed_mul(y, denominator, rounding) {
+            Ok(result) => result,
+            Err(_) => panic_with_error!(e, error),
+        }
+    }
+
+    /// Calculates x * denominator / y, rounded in the direction of `rounding`. Panics
+    /// with `error` if the result overflows or if y is 0.
+    fn fixed_div_or_panic<E: Into<soroban_sdk::Error>>(
+        self,
+        y: Self,
+        denominator: Self,
+        rounding: Rounding,
+        e: &Env,
+        error: E,
+    ) -> Self {
+        match self.try_fixed_div(y, denominator, rounding) {
+            Ok(result) => result,
+            Err(_) => panic_with_error!(e, error),
+        }
+    }
+
+    /// Calculates floor(x * y / denominator). Panics with `error` if the result
+    /// overflows or if the denominator is 0.
+    fn fixed_mul_floor_or_panic<E: Into<soroban_sdk::Error>>(
+        self,
+        y: Self,
+        denominator: Self,
+        e: &Env,
+        error: E,
+    ) -> Self {
+        self.fixed_mul_or_panic(y, denominator, Rounding::Floor, e, error)
+    }
+
+    /// Calculates ceil(x * y / denominator). Panics with `error` if the result
+    /// overflows or if the denominator is 0.
+    fn fixed_mul_ceil_or_panic<E: Into<soroban_sdk::Error>>(
+        self,
+        y: Self,
+        denominator: Self,
+        e: &Env,
+        error: E,
+    ) -> Self {
+        self.fixed_mul_or_panic(y, denominator, Rounding::Ceil, e, error)
+    }
+
+    /// Calculates floor(x * denominator / y). Panics with `error` if the result
+    /// overflows or if y is 0.
+    fn fixed_div_floor_or_panic<E: Into<soroban_sdk::Error>>(
+        self,
+        y: Self,
+        denominator: Self,
+        e: &Env,
+        error: E,
+    ) -> Self {
+        self.fixed_div_or_panic(y, denominator, Rounding::Floor, e, error)
+    }
+
+    /// Calculates ceil(x * denominator / y). Panics with `error` if the result
+    /// overflows or if y is 0.
+    fn fixed_div_ceil_or_panic<E: Into<soroban_sdk::Error>>(
+        self,
+        y: Self,
+        denominator: Self,
+        e: &Env,
+        error: E,
+    ) -> Self {
+        self.fixed_div_or_panic(y, denominator, Rounding::Ceil, e, error)
+    }
+}
+
+#[allow(deprecated)]
+impl<T: FixedPoint> SorobanFixedPoint for T {}
+
+#[cfg(all(test, feature = "testutils"))]
+mod tests {
+    use soroban_sdk::contracterror;
+
+    use super::*;
+
+    #[contracterror]
+    #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+    #[repr(u32)]
+    enum TestError {
+        OverflowError = 1,
+        DivideByZeroError = 2,
+    }
+
+    #[test]
+    fn test_fixed_mul_or_panic() {
+        let e = Env::default();
+        let x: i128 = 1_5391283;
+        let y: i128 = 314_1592653;
+
+        let floor = x.fixed_mul_floor_or_panic(y, 1_0000000, &e, TestError::OverflowError);
+        let ceil = x.fixed_mul_ceil_or_panic(y, 1_0000000, &e, TestError::OverflowError);
+
+        assert_eq!(floor, 483_5314159);
+        assert_eq!(ceil, 483_5314160);
+    }
+
+    #[test]
+    fn test_fixed_div_or_panic() {
+        let e = Env::default();
+        let x: u64 = 314_1592653;
+        let y: u64 = 1_5391280;
+
+        let floor = x.fixed_div_floor_or_panic(y, 1_0000000, &e, TestError::OverflowError);
+        let ceil = x.fixed_div_ceil_or_panic(y, 1_0000000, &e, TestError::OverflowError);
+
+        assert_eq!(floor, 204_1150997);
+        assert_eq!(ceil, 204_1150998);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1)")]
+    fn test_fixed_mul_or_panic_overflow() {
+        let e = Env::default();
+
+        i128::MAX.fixed_mul_floor_or_panic(2, 1, &e, TestError::OverflowError);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #2)")]
+    fn test_fixed_div_or_panic_divide_by_zero() {
+        let e = Env::default();
+
+        1_0000000_i128.fixed_div_ceil_or_panic(0, 1_0000000, &e, TestError::DivideByZeroError);
+    }
+}
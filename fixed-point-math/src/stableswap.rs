@@ -0,0 +1,362 @@
+//! Invariant math for StableSwap (Curve-style) pools of two or more correlated coins
+//!
+//! The invariant D satisfies A * n^n * sum(x) + D = A * D * n^n + D^(n+1) / (n^n * prod(x)),
+//! where A is the amplification coefficient and n is the number of coins, as defined in
+//! the StableSwap whitepaper. Curve contracts store A * n^(n-1) instead, so their `A`
+//! must be divided by n^(n-1) before it is passed here. Both D and the balance solved
+//! for by `compute_y` are found with Newton's method, following the `get_D` and `get_y`
+//! functions of the original Curve pools.
+
+#[allow(deprecated)]
+use crate::{error::FixedPointError, fixed_point::FixedPoint, integer::Integer, u256::U256};
+
+/// The maximum number of Newton iterations before a solver fails with `NoConvergence`
+pub const MAX_ITERATIONS: u32 = 255;
+
+/// Safely calculates the invariant D for the pool `balances`, with an amplification
+/// coefficient of `amp`. All balances must use the same scale.
+///
+/// `amp` is the A of the whitepaper invariant, not the A * n^(n-1) stored by Curve
+/// contracts.
+///
+/// Returns an error if there are fewer than two coins, if any value is negative, if
+/// `amp` is 0, if an intermediate value overflows, or if the solver does not converge
+/// within `MAX_ITERATIONS`.
+pub fn try_compute_d<T: Integer>(balances: &[T], amp: T) -> Result<T, FixedPointError> {
+    let d = invariant(balances, to_u128(amp)?, MAX_ITERATIONS)?;
+    T::from_u128(d).ok_or(FixedPointError::Overflow)
+}
+
+/// Safely calculates the invariant D for the pool `balances`, with an amplification
+/// coefficient of `amp`. All balances must use the same scale.
+///
+/// Returns None if there are fewer than two coins, if any value is negative, if `amp`
+/// is 0, if an intermediate value overflows, or if the solver does not converge.
+pub fn compute_d<T: Integer>(balances: &[T], amp: T) -> Option<T> {
+    try_compute_d(balances, amp).ok()
+}
+
+/// Safely calculates the new balance of coin `j` that keeps the invariant of the pool
+/// `balances` unchanged, after the balance of coin `i` is set to `x`.
+///
+/// The amount of coin `j` received for a swap is the current balance of coin `j`
+/// minus the result. The result is rounded down by the solver, so callers should
+/// subtract 1 from the amount received to round in favor of the pool. `amp` is the A
+/// of the whitepaper invariant, as in `try_compute_d`.
+///
+/// Returns an error if there are fewer than two coins, if `i` or `j` are out of
+/// bounds or equal, if any value is negative, if `amp` is 0, if an intermediate value
+/// overflows, or if the solver does not converge within `MAX_ITERATIONS`.
+pub fn try_compute_y<T: Integer>(
+    balances: &[T],
+    amp: T,
+    i: usize,
+    j: usize,
+    x: T,
+) -> Result<T, FixedPointError> {
+    let y = solve_y(balances, to_u128(amp)?, i, j, to_u128(x)?, MAX_ITERATIONS)?;
+    T::from_u128(y).ok_or(FixedPointError::Overflow)
+}
+
+/// Safely calculates the new balance of coin `j` that keeps the invariant of the pool
+/// `balances` unchanged, after the balance of coin `i` is set to `x`. See
+/// `try_compute_y` for details.
+///
+/// Returns None if the inputs are invalid, if an intermediate value overflows, or if
+/// the solver does not converge.
+pub fn compute_y<T: Integer>(balances: &[T], amp: T, i: usize, j: usize, x: T) -> Option<T> {
+    try_compute_y(balances, amp, i, j, x).ok()
+}
+
+fn to_u128<T: Integer>(value: T) -> Result<u128, FixedPointError> {
+    value.to_u128().ok_or(FixedPointError::InvalidInput)
+}
+
+/// Validates the pool, and returns the number of coins and A * n^n
+fn check_pool<T: Integer>(balances: &[T], amp: u128) -> Result<(u128, u128), FixedPointError> {
+    if balances.len() < 2 || amp == 0 {
+        return Err(FixedPointError::InvalidInput);
+    }
+    let n = u128::try_from(balances.len()).map_err(|_| FixedPointError::Overflow)?;
+    let ann = u32::try_from(n)
+        .ok()
+        .and_then(|exp| n.checked_pow(exp))
+        .and_then(|n_n| amp.checked_mul(n_n))
+        .ok_or(FixedPointError::Overflow)?;
+    Ok((n, ann))
+}
+
+#[allow(deprecated)]
+fn invariant<T: Integer>(
+    balances: &[T],
+    amp: u128,
+    max_iterations: u32,
+) -> Result<u128, FixedPointError> {
+    let (n, ann) = check_pool(balances, amp)?;
+    let mut sum: u128 = 0;
+    for balance in balances {
+        sum = sum
+            .checked_add(to_u128(*balance)?)
+            .ok_or(FixedPointError::Overflow)?;
+    }
+    if sum == 0 {
+        return Ok(0);
+    }
+
+    let mut d = sum;
+    for _ in 0..max_iterations {
+        // d_p = D^(n+1) / (n^n * prod(x))
+        let mut d_p = d;
+        for balance in balances {
+            let denominator = to_u128(*balance)?
+                .checked_mul(n)
+                .ok_or(FixedPointError::Overflow)?;
+            d_p = d_p.try_fixed_mul_floor(d, denominator)?;
+        }
+        let previous = d;
+        // D = (Ann * S + d_p * n) * D / ((Ann - 1) * D + (n + 1) * d_p)
+        let numerator = checked_mul_add(ann, sum, d_p, n)?;
+        let denominator = checked_mul_add(ann - 1, d, n + 1, d_p)?;
+        d = numerator.try_fixed_mul_floor(d, denominator)?;
+        if d.abs_diff(previous) <= 1 {
+            return Ok(d);
+        }
+    }
+    Err(FixedPointError::NoConvergence)
+}
+
+#[allow(deprecated)]
+fn solve_y<T: Integer>(
+    balances: &[T],
+    amp: u128,
+    i: usize,
+    j: usize,
+    x: u128,
+    max_iterations: u32,
+) -> Result<u128, FixedPointError> {
+    if i == j || i >= balances.len() || j >= balances.len() {
+        return Err(FixedPointError::InvalidInput);
+    }
+    let (n, ann) = check_pool(balances, amp)?;
+    let d = invariant(balances, amp, max_iterations)?;
+
+    // c = D^(n+1) / (n^n * prod(x') * Ann * n), and b = sum(x') + D / Ann, where x'
+    // are the balances of every coin except j
+    let mut c = d;
+    let mut sum: u128 = 0;
+    for (k, balance) in balances.iter().enumerate() {
+        let balance = match k {
+            k if k == i => x,
+            k if k == j => continue,
+            _ => to_u128(*balance)?,
+        };
+        sum = sum.checked_add(balance).ok_or(FixedPointError::Overflow)?;
+        let denominator = balance.checked_mul(n).ok_or(FixedPointError::Overflow)?;
+        c = c.try_fixed_mul_floor(d, denominator)?;
+    }
+    // the last step can exceed a u128, so c is kept in 256 bits
+    let denominator = ann.checked_mul(n).ok_or(FixedPointError::Overflow)?;
+    let (c, _) = U256::mul_u128(c, d)
+        .div_rem_u128(denominator)
+        .ok_or(FixedPointError::DivideByZero)?;
+    let b = sum.checked_add(d / ann).ok_or(FixedPointError::Overflow)?;
+
+    let mut y = d;
+    for _ in 0..max_iterations {
+        let previous = y;
+        // y = (y^2 + c) / (2 * y + b - D)
+        let numerator = U256::mul_u128(y, y)
+            .checked_add(c)
+            .ok_or(FixedPointError::Overflow)?;
+        let denominator = checked_mul_add(2, y, 1, b)?
+            .checked_sub(d)
+            .ok_or(FixedPointError::NoConvergence)?;
+        y = numerator
+            .div_rem_u128(denominator)
+            .ok_or(FixedPointError::DivideByZero)?
+            .0
+            .to_u128()
+            .ok_or(FixedPointError::Overflow)?;
+        if y.abs_diff(previous) <= 1 {
+            return Ok(y);
+        }
+    }
+    Err(FixedPointError::NoConvergence)
+}
+
+/// Calculates a * b + c * d, or returns an error if it overflows
+fn checked_mul_add(a: u128, b: u128, c: u128, d: u128) -> Result<u128, FixedPointError> {
+    a.checked_mul(b)
+        .and_then(|ab| c.checked_mul(d).and_then(|cd| ab.checked_add(cd)))
+        .ok_or(FixedPointError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WAD: i128 = 1_000_000_000_000_000_000;
+
+    #[test]
+    fn test_compute_d_balanced() {
+        let balances = [1_000_000 * WAD, 1_000_000 * WAD];
+
+        assert_eq!(compute_d(&balances, 100), Some(2_000_000 * WAD));
+        assert_eq!(compute_d(&[1_u64, 1], 1), Some(2));
+        assert_eq!(compute_d(&[0_u64, 0], 100), Some(0));
+    }
+
+    // Exact solutions of the whitepaper invariant, which can be checked by substituting
+    // them into A * n^n * sum(x) + D = A * D * n^n + D^(n+1) / (n^n * prod(x)):
+    // - A = 15: D = 120 for balances of [25, 96] and [60, 60]
+    // - A = 20: D = 60 for balances of [1, 20, 40] and [20, 20, 20]
+    // - A = 4: D = 36 for balances of [2, 8, 27]
+    // The solvers would be off by a factor of n^(n-1) in A if `amp` were A * n^(n-1).
+
+    #[test]
+    fn test_compute_d_whitepaper() {
+        assert_eq!(compute_d(&[25 * WAD, 96 * WAD], 15), Some(120 * WAD));
+        assert_eq!(
+            compute_d(&[1_0000000_u64, 20_0000000, 40_0000000], 20),
+            Some(60_0000000)
+        );
+        assert_eq!(
+            compute_d(&[2_0000000_u64, 8_0000000, 27_0000000], 4),
+            Some(36_0000000)
+        );
+    }
+
+    #[test]
+    fn test_compute_y_whitepaper() {
+        assert_eq!(
+            compute_y(&[60 * WAD, 60 * WAD], 15, 0, 1, 25 * WAD),
+            Some(96 * WAD)
+        );
+        assert_eq!(
+            compute_y(
+                &[20_0000000_u64, 20_0000000, 20_0000000],
+                20,
+                0,
+                2,
+                1_0000000
+            ),
+            Some(40_0000000)
+        );
+    }
+
+    // Regression values for unbalanced pools without an exact solution. They match a
+    // separate Python port of Curve's `get_D` and `get_y`, run with Ann = A * n^n.
+
+    #[test]
+    fn test_compute_d_known_values() {
+        assert_eq!(
+            compute_d(&[1_000_000 * WAD, 500_000 * WAD], 100),
+            Some(1_499_534_015_561_310_279_719_492)
+        );
+        assert_eq!(
+            compute_d(&[WAD, 1000 * WAD], 10),
+            Some(448_469_997_848_993_176_769)
+        );
+        assert_eq!(
+            compute_d(&[1000_0000000_u64, 1200_0000000, 900_0000000], 200),
+            Some(3099_9875845)
+        );
+    }
+
+    #[test]
+    fn test_compute_y_known_values() {
+        assert_eq!(
+            compute_y(
+                &[1_000_000 * WAD, 1_000_000 * WAD],
+                100,
+                0,
+                1,
+                1_000_100 * WAD
+            ),
+            Some(999_900_000_049_751_219_521_817)
+        );
+        assert_eq!(
+            compute_y(&[1_000_000 * WAD, 500_000 * WAD], 100, 1, 0, 500_100 * WAD),
+            Some(999_899_580_412_960_178_708_915)
+        );
+
+        let balances: [u64; 3] = [1000_0000000, 1200_0000000, 900_0000000];
+        assert_eq!(
+            compute_y(&balances, 200, 0, 1, 1010_0000000),
+            Some(1189_9990733)
+        );
+        assert_eq!(
+            compute_y(&balances, 200, 2, 0, 950_0000000),
+            Some(949_9983763)
+        );
+    }
+
+    #[test]
+    fn test_compute_y_keeps_invariant() {
+        let balances: [i128; 3] = [1000_0000000, 1200_0000000, 900_0000000];
+        let d = compute_d(&balances, 200).unwrap();
+
+        let y = compute_y(&balances, 200, 0, 1, balances[0]).unwrap();
+
+        assert!(y.abs_diff(balances[1]) <= 1);
+        let new_balances = [
+            1010_0000000,
+            compute_y(&balances, 200, 0, 1, 1010_0000000).unwrap(),
+            900_0000000,
+        ];
+        assert!(compute_d(&new_balances, 200).unwrap().abs_diff(d) <= 2);
+    }
+
+    #[test]
+    fn test_no_convergence() {
+        let balances: [u128; 2] = [1_000_000, 500_000];
+
+        assert_eq!(
+            invariant(&balances, 100, 1),
+            Err(FixedPointError::NoConvergence)
+        );
+        assert_eq!(
+            solve_y(&balances, 100, 0, 1, 2_000_000, 4),
+            Err(FixedPointError::NoConvergence)
+        );
+        assert!(invariant(&balances, 100, MAX_ITERATIONS).is_ok());
+    }
+
+    #[test]
+    fn test_errors() {
+        let balances = [1_000_000 * WAD, 1_000_000 * WAD];
+
+        assert_eq!(
+            try_compute_d(&[WAD], 100),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_compute_d(&balances, 0),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_compute_d(&[WAD, -WAD], 100),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_compute_d(&[WAD, 0], 100),
+            Err(FixedPointError::DivideByZero)
+        );
+        assert_eq!(
+            try_compute_d(&[u128::MAX, u128::MAX], 100),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!(
+            try_compute_y(&balances, 100, 0, 0, WAD),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_compute_y(&balances, 100, 0, 2, WAD),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_compute_y(&balances, 100, 0, 1, -WAD),
+            Err(FixedPointError::InvalidInput)
+        );
+    }
+}
@@ -0,0 +1,343 @@
+//! Exponential moving averages and time-weighted average prices (TWAP) for oracles
+//!
+//! A TWAP is derived from two observations of a cumulative price, which grows by
+//! price * elapsed seconds on every update. The cumulative value is expected to
+//! overflow and wraps around, and the difference between two observations stays
+//! correct as long as the true difference fits in the type.
+
+#[allow(deprecated)]
+use crate::{
+    curve, error::FixedPointError, fixed_point::FixedPoint, integer::Integer, rounding::Rounding,
+};
+
+/// Safely calculates the exponential moving average after `observation`, as
+/// previous + alpha * (observation - previous), rounded in the direction of `rounding`.
+/// `alpha` is the smoothing factor at `scale`, where a larger factor gives more weight to
+/// the new observation.
+///
+/// The rounding is applied to the average itself, like `curve::try_lerp`, so it also
+/// holds for negative averages.
+///
+/// Returns an error if `alpha` is not between 0 and `scale`, if the scale is not
+/// positive, or if the difference between the values does not fit in `T`.
+#[allow(deprecated)]
+pub fn try_ema<T: FixedPoint + Integer>(
+    previous: T,
+    observation: T,
+    alpha: T,
+    scale: T,
+    rounding: Rounding,
+) -> Result<T, FixedPointError> {
+    if scale == T::ZERO {
+        return Err(FixedPointError::DivideByZero);
+    }
+    if scale < T::ZERO || alpha < T::ZERO || alpha > scale {
+        return Err(FixedPointError::InvalidInput);
+    }
+    // the average moves from `previous` to `observation` as alpha goes from 0 to 1
+    curve::try_lerp(T::ZERO, previous, scale, observation, alpha, rounding)
+}
+
+/// Safely calculates the exponential moving average after `observation`, as
+/// previous + alpha * (observation - previous), rounded in the direction of `rounding`.
+/// See `try_ema` for details.
+///
+/// Returns None if `alpha` is not between 0 and `scale`, if the scale is not positive,
+/// or if an overflow occurs.
+#[allow(deprecated)]
+pub fn ema<T: FixedPoint + Integer>(
+    previous: T,
+    observation: T,
+    alpha: T,
+    scale: T,
+    rounding: Rounding,
+) -> Option<T> {
+    try_ema(previous, observation, alpha, scale, rounding).ok()
+}
+
+/// Safely calculates the conventional smoothing factor for an EMA over `periods`
+/// observations, 2 / (periods + 1) at `scale`, rounded down. Returns an error if
+/// `periods` is not positive, or if the result does not fit in `T`.
+#[allow(deprecated)]
+pub fn try_smoothing_factor<T: FixedPoint + Integer>(
+    periods: T,
+    scale: T,
+) -> Result<T, FixedPointError> {
+    if periods <= T::ZERO {
+        return Err(FixedPointError::InvalidInput);
+    }
+    let two = T::ONE + T::ONE;
+    let denominator = periods
+        .checked_add(T::ONE)
+        .ok_or(FixedPointError::Overflow)?;
+    two.try_fixed_mul_floor(scale, denominator)
+}
+
+/// Safely calculates the conventional smoothing factor for an EMA over `periods`
+/// observations, 2 / (periods + 1) at `scale`, rounded down. Returns None if `periods`
+/// is not positive, or if an overflow occurs.
+#[allow(deprecated)]
+pub fn smoothing_factor<T: FixedPoint + Integer>(periods: T, scale: T) -> Option<T> {
+    try_smoothing_factor(periods, scale).ok()
+}
+
+/// An observation of a cumulative price, from which a TWAP can be derived
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Observation<T> {
+    /// The timestamp of the observation, in seconds
+    pub timestamp: u64,
+    /// The sum of price * elapsed seconds up to the timestamp, wrapping on overflow
+    pub cumulative: T,
+}
+
+#[allow(deprecated)]
+impl<T: FixedPoint + Integer> Observation<T> {
+    /// Creates an observation of `cumulative` at `timestamp`
+    pub fn new(timestamp: u64, cumulative: T) -> Self {
+        Observation {
+            timestamp,
+            cumulative,
+        }
+    }
+
+    /// Safely accumulates `price` over the seconds since this observation, and returns
+    /// the observation at `timestamp`. `price` is the price that was in effect since
+    /// this observation, not the price at `timestamp`.
+    ///
+    /// The cumulative value wraps around on overflow. Returns an error if `timestamp` is
+    /// before this observation, or if the elapsed seconds do not fit in `T`.
+    pub fn try_update(&self, timestamp: u64, price: T) -> Result<Self, FixedPointError> {
+        let elapsed = elapsed::<T>(self.timestamp, timestamp)?;
+        Ok(Observation {
+            timestamp,
+            cumulative: self.cumulative.wrapping_add(price.wrapping_mul(elapsed)),
+        })
+    }
+
+    /// Safely accumulates `price` over the seconds since this observation, and returns
+    /// the observation at `timestamp`. See `try_update` for details.
+    ///
+    /// Returns None if `timestamp` is before this observation, or if the elapsed seconds
+    /// overflow.
+    pub fn update(&self, timestamp: u64, price: T) -> Option<Self> {
+        self.try_update(timestamp, price).ok()
+    }
+}
+
+/// Safely calculates the time-weighted average price between two observations, rounded
+/// in the direction of `rounding`.
+///
+/// The difference between the cumulative values is taken with wrapping arithmetic, so
+/// the result is correct across overflows of the cumulative value, as long as the sum of
+/// price * elapsed seconds between the observations fits in `T`.
+///
+/// Returns an error if `end` is not after `start`, or if the elapsed seconds do not fit
+/// in `T`.
+#[allow(deprecated)]
+pub fn try_twap<T: FixedPoint + Integer>(
+    start: &Observation<T>,
+    end: &Observation<T>,
+    rounding: Rounding,
+) -> Result<T, FixedPointError> {
+    let elapsed = elapsed::<T>(start.timestamp, end.timestamp)?;
+    if elapsed == T::ZERO {
+        return Err(FixedPointError::DivideByZero);
+    }
+    end.cumulative
+        .wrapping_sub(start.cumulative)
+        .try_fixed_mul(T::ONE, elapsed, rounding)
+}
+
+/// Safely calculates the time-weighted average price between two observations, rounded
+/// in the direction of `rounding`. See `try_twap` for details.
+///
+/// Returns None if `end` is not after `start`, or if the elapsed seconds overflow.
+#[allow(deprecated)]
+pub fn twap<T: FixedPoint + Integer>(
+    start: &Observation<T>,
+    end: &Observation<T>,
+    rounding: Rounding,
+) -> Option<T> {
+    try_twap(start, end, rounding).ok()
+}
+
+/// Returns the seconds from `start` to `end` as a `T`
+fn elapsed<T: Integer>(start: u64, end: u64) -> Result<T, FixedPointError> {
+    let elapsed = end
+        .checked_sub(start)
+        .ok_or(FixedPointError::InvalidInput)?;
+    T::from_u128(u128::from(elapsed)).ok_or(FixedPointError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ema() {
+        let alpha: i128 = 2000000;
+
+        // 100 + 0.2 * (110 - 100) = 102
+        assert_eq!(
+            ema(100_0000000, 110_0000000, alpha, 1_0000000, Rounding::Floor),
+            Some(102_0000000)
+        );
+        // 100 - 0.2 * (100 - 90.0000001) = 98.00000002
+        assert_eq!(
+            ema(100_0000000, 90_0000001, alpha, 1_0000000, Rounding::Floor),
+            Some(98_0000000)
+        );
+        assert_eq!(
+            ema(100_0000000, 90_0000001, alpha, 1_0000000, Rounding::Ceil),
+            Some(98_0000001)
+        );
+    }
+
+    #[test]
+    fn test_ema_negative() {
+        let alpha: i128 = 5000000;
+
+        // -100 + 0.5 * (-105 + 100) = -102.5
+        assert_eq!(
+            ema(-100, -105, alpha, 1_0000000, Rounding::HalfUp),
+            Some(-103)
+        );
+        assert_eq!(
+            ema(-100, -105, alpha, 1_0000000, Rounding::HalfEven),
+            Some(-102)
+        );
+        assert_eq!(
+            ema(-100, -105, alpha, 1_0000000, Rounding::TowardZero),
+            Some(-102)
+        );
+        assert_eq!(
+            ema(-100, -105, alpha, 1_0000000, Rounding::Floor),
+            Some(-103)
+        );
+        // -105 + 0.5 * (-100 + 105) = -102.5
+        assert_eq!(
+            ema(-105, -100, alpha, 1_0000000, Rounding::HalfUp),
+            Some(-103)
+        );
+        assert_eq!(
+            ema(-105, -100, alpha, 1_0000000, Rounding::AwayFromZero),
+            Some(-103)
+        );
+    }
+
+    #[test]
+    fn test_ema_alpha_bounds() {
+        assert_eq!(ema(100_u64, 200, 0, 1_0000000, Rounding::Ceil), Some(100));
+        assert_eq!(
+            ema(100_u64, 200, 1_0000000, 1_0000000, Rounding::Floor),
+            Some(200)
+        );
+        assert_eq!(
+            try_ema(100_u64, 200, 1_0000001, 1_0000000, Rounding::Floor),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_ema(100_i128, 200, -1, 1_0000000, Rounding::Floor),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_ema(100_i128, 200, 0, 0, Rounding::Floor),
+            Err(FixedPointError::DivideByZero)
+        );
+    }
+
+    #[test]
+    fn test_ema_converges() {
+        let alpha = smoothing_factor(9_u64, 1_0000000).unwrap();
+        let mut average: u64 = 0;
+
+        for _ in 0..200 {
+            average = ema(average, 5_0000000, alpha, 1_0000000, Rounding::Ceil).unwrap();
+        }
+
+        assert_eq!(alpha, 2000000);
+        assert_eq!(average, 5_0000000);
+    }
+
+    #[test]
+    fn test_smoothing_factor() {
+        // 2 / 31 = 0.0645161290...
+        assert_eq!(smoothing_factor(30_i128, 1_0000000), Some(645161));
+        assert_eq!(smoothing_factor(1_i128, 1_0000000), Some(1_0000000));
+        assert_eq!(
+            try_smoothing_factor(0_i128, 1_0000000),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            try_smoothing_factor(u64::MAX, 1_0000000),
+            Err(FixedPointError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_twap() {
+        let start = Observation::new(1000, 0_i128);
+
+        // 1.5 for 60 seconds, then 2.1 for 40 seconds
+        let middle = start.update(1060, 1_5000000).unwrap();
+        let end = middle.update(1100, 2_1000000).unwrap();
+
+        assert_eq!(middle.cumulative, 90_0000000);
+        assert_eq!(end.cumulative, 174_0000000);
+        // (1.5 * 60 + 2.1 * 40) / 100 = 1.74
+        assert_eq!(twap(&start, &end, Rounding::Floor), Some(1_7400000));
+        assert_eq!(twap(&middle, &end, Rounding::Floor), Some(2_1000000));
+    }
+
+    #[test]
+    fn test_twap_rounds() {
+        let start = Observation::new(0, 0_u64);
+        let end = start.update(3, 1_0000000).unwrap().update(6, 2).unwrap();
+
+        // (3 + 6 * 0.0000001) / 6 = 0.5000001
+        assert_eq!(twap(&start, &end, Rounding::Floor), Some(5000001));
+        let end = end.update(10, 0).unwrap();
+        // (3 + 0.0000006) / 10 = 0.30000006
+        assert_eq!(twap(&start, &end, Rounding::Floor), Some(3000000));
+        assert_eq!(twap(&start, &end, Rounding::HalfUp), Some(3000001));
+        assert_eq!(twap(&start, &end, Rounding::Ceil), Some(3000001));
+    }
+
+    #[test]
+    fn test_twap_cumulative_wraps() {
+        let price: u64 = 12_3456789;
+        let start = Observation::new(5000, u64::MAX - 1000);
+
+        let end = start.update(5600, price).unwrap();
+
+        assert!(end.cumulative < start.cumulative);
+        assert_eq!(twap(&start, &end, Rounding::Floor), Some(price));
+
+        let start = Observation::new(0, i128::MAX);
+        let end = start
+            .update(10, -7_0000000)
+            .unwrap()
+            .update(20, 9_0000000)
+            .unwrap();
+        assert_eq!(twap(&start, &end, Rounding::Floor), Some(1_0000000));
+    }
+
+    #[test]
+    fn test_twap_errors() {
+        let start = Observation::new(100, 0_i128);
+
+        assert_eq!(start.try_update(99, 1), Err(FixedPointError::InvalidInput));
+        assert_eq!(
+            try_twap(&start, &start.update(100, 1).unwrap(), Rounding::Floor),
+            Err(FixedPointError::DivideByZero)
+        );
+        assert_eq!(
+            try_twap(&start, &Observation::new(50, 0), Rounding::Floor),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            Observation::new(0, 0_u32).try_update(u64::MAX, 1),
+            Err(FixedPointError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,284 @@
+#[allow(deprecated)]
+use crate::{
+    error::FixedPointError,
+    fixed_point::FixedPoint,
+    format,
+    rounding::{Remainder, Rounding},
+    u256::U256,
+};
+
+#[allow(deprecated)]
+impl FixedPoint for u128 {
+    fn try_fixed_mul(
+        self,
+        y: u128,
+        denominator: u128,
+        rounding: Rounding,
+    ) -> Result<u128, FixedPointError> {
+        mul_div(self, y, denominator, rounding)
+    }
+}
+
+/// Performs x * y / z, rounded in the direction of `rounding`
+fn mul_div(x: u128, y: u128, z: u128, rounding: Rounding) -> Result<u128, FixedPointError> {
+    if z == 0 {
+        return Err(FixedPointError::DivideByZero);
+    }
+    match x.checked_mul(y) {
+        Some(r) => round(r / z, Remainder::new(r % z, z), rounding),
+        None => {
+            let (res_u256, remainder) = U256::mul_u128(x, y)
+                .div_rem_u128(z)
+                .ok_or(FixedPointError::DivideByZero)?;
+            let res = res_u256.to_u128().ok_or(FixedPointError::Overflow)?;
+            round(res, Remainder::new(remainder, z), rounding)
+        }
+    }
+}
+
+/// Rounds a truncated quotient in the direction of `rounding`
+fn round(
+    quotient: u128,
+    remainder: Remainder,
+    rounding: Rounding,
+) -> Result<u128, FixedPointError> {
+    if rounding.round_away_from_zero(false, quotient % 2 == 1, remainder) {
+        quotient.checked_add(1).ok_or(FixedPointError::Overflow)
+    } else {
+        Ok(quotient)
+    }
+}
+
+/// Parses a decimal string, like "1.25", into a fixed-point number with `decimals`
+/// decimal places. Returns None if the string is malformed or negative, if it has more
+/// fractional digits than `decimals`, or if the result overflows.
+///
+/// Accepts the same strings as `FixedPoint::parse_fixed`, and is usable in `const`
+/// contexts. This is used by the `fixed!` macro for values above `i128::MAX`.
+pub const fn parse_fixed(s: &str, decimals: u32) -> Option<u128> {
+    match format::parse_magnitude(s, decimals, None) {
+        Ok((false, magnitude)) | Ok((true, magnitude @ 0)) => Some(magnitude),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use super::*;
+
+    /********** fixed_mul_floor **********/
+
+    #[test]
+    fn test_fixed_mul_floor_rounds_down() {
+        let x: u128 = 1_5391283;
+        let y: u128 = 314_1592653;
+        let denominator: u128 = 1_0000001;
+
+        let result = x.fixed_mul_floor(y, denominator).unwrap();
+
+        assert_eq!(result, 483_5313675)
+    }
+
+    #[test]
+    fn test_fixed_mul_floor_large_number() {
+        let x: u128 = 340_282_366_920_938_463_463;
+        let y: u128 = 1_000_000_000_000_000_000;
+        let denominator: u128 = 1_000_000_000_000_000_000;
+
+        let result = x.fixed_mul_floor(y, denominator).unwrap();
+
+        assert_eq!(result, 340_282_366_920_938_463_463)
+    }
+
+    #[test]
+    fn test_fixed_mul_floor_phantom_overflow_uses_u256() {
+        let x: u128 = 340_282_366_920_938_463_463;
+        let y: u128 = 2_000_000_000_000_000_000;
+        let denominator: u128 = 1_000_000_000_000_000_000;
+
+        let result = x.fixed_mul_floor(y, denominator).unwrap();
+
+        assert_eq!(result, 680_564_733_841_876_926_926);
+    }
+
+    #[test]
+    fn test_fixed_mul_floor_result_overflow() {
+        let x: u128 = 340_282_366_920_938_463_463_000_000_000_000_000_000;
+        let y: u128 = 2_000_000_000_000_000_000;
+        let denominator: u128 = 1_000_000_000_000_000_000;
+
+        let result = x.fixed_mul_floor(y, denominator);
+
+        assert_eq!(result, None);
+    }
+
+    /********** fixed_mul_ceil **********/
+
+    #[test]
+    fn test_fixed_mul_ceil_rounds_up() {
+        let x: u128 = 1_5391283;
+        let y: u128 = 314_1592653;
+        let denominator: u128 = 1_0000001;
+
+        let result = x.fixed_mul_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, 483_5313676)
+    }
+
+    #[test]
+    fn test_fixed_mul_ceil_large_number() {
+        let x: u128 = 340_282_366_920_938_463_463;
+        let y: u128 = 1_000_000_000_000_000_000;
+        let denominator: u128 = 1_000_000_000_000_000_000;
+
+        let result = x.fixed_mul_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, 340_282_366_920_938_463_463)
+    }
+
+    #[test]
+    fn test_fixed_mul_ceil_phantom_overflow_uses_u256() {
+        let x: u128 = 340_282_366_920_938_463_463;
+        let y: u128 = 2_000_000_000_000_000_000;
+        let denominator: u128 = 1_000_000_000_000_000_000;
+
+        let result = x.fixed_mul_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, 680_564_733_841_876_926_926);
+    }
+
+    #[test]
+    fn test_fixed_mul_ceil_result_overflow() {
+        let x: u128 = 340_282_366_920_938_463_463_000_000_000_000_000_000;
+        let y: u128 = 2_000_000_000_000_000_000;
+        let denominator: u128 = 1_000_000_000_000_000_000;
+
+        let result = x.fixed_mul_ceil(y, denominator);
+
+        assert_eq!(result, None);
+    }
+
+    /********** fixed_div_floor **********/
+
+    #[test]
+    fn test_fixed_div_floor_rounds_down() {
+        let x: u128 = 314_1592653;
+        let y: u128 = 1_5391280;
+        let denominator: u128 = 1_0000000;
+
+        let result = x.fixed_div_floor(y, denominator).unwrap();
+
+        assert_eq!(result, 204_1150997)
+    }
+
+    #[test]
+    fn test_fixed_div_floor_large_number() {
+        let x: u128 = 340_282_366_920_938_463_463;
+        let y: u128 = 1_000_000_000_000_000_000;
+        let denominator: u128 = 1_000_000_000_000_000_000;
+
+        let result = x.fixed_div_floor(y, denominator).unwrap();
+
+        assert_eq!(result, 340_282_366_920_938_463_463)
+    }
+
+    #[test]
+    fn test_fixed_div_floor_phantom_overflow_uses_u256() {
+        let x: u128 = 340_282_366_920_938_463_465;
+        let y: u128 = 2_000_000_000_000_000_000;
+        let denominator: u128 = 1_000_000_000_000_000_000;
+
+        let result = x.fixed_div_floor(y, denominator).unwrap();
+
+        assert_eq!(result, 170_141_183_460_469_231_732);
+    }
+
+    #[test]
+    fn test_fixed_div_floor_result_overflow() {
+        let x: u128 = 340_282_366_920_938_463_463_000_000_000_000_000_000;
+        let y: u128 = 2_000_000_000_000_000_000;
+        let denominator: u128 = 4_000_000_000_000_000_000;
+
+        let result = x.fixed_div_floor(y, denominator);
+
+        assert_eq!(result, None);
+    }
+
+    /********** fixed_div_ceil **********/
+
+    #[test]
+    fn test_fixed_div_ceil_rounds_up() {
+        let x: u128 = 314_1592653;
+        let y: u128 = 1_5391280;
+        let denominator: u128 = 1_0000000;
+
+        let result = x.fixed_div_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, 204_1150998)
+    }
+
+    #[test]
+    fn test_fixed_div_ceil_large_number() {
+        let x: u128 = 340_282_366_920_938_463_463;
+        let y: u128 = 1_000_000_000_000_000_000;
+        let denominator: u128 = 1_000_000_000_000_000_000;
+
+        let result = x.fixed_div_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, 340_282_366_920_938_463_463)
+    }
+
+    #[test]
+    fn test_fixed_div_ceil_phantom_overflow_uses_u256() {
+        let x: u128 = 340_282_366_920_938_463_465;
+        let y: u128 = 2_000_000_000_000_000_000;
+        let denominator: u128 = 1_000_000_000_000_000_000;
+
+        let result = x.fixed_div_ceil(y, denominator).unwrap();
+
+        assert_eq!(result, 170_141_183_460_469_231_733);
+    }
+
+    #[test]
+    fn test_fixed_div_ceil_result_overflow() {
+        let x: u128 = 340_282_366_920_938_463_463_000_000_000_000_000_000;
+        let y: u128 = 2_000_000_000_000_000_000;
+        let denominator: u128 = 4_000_000_000_000_000_000;
+
+        let result = x.fixed_div_ceil(y, denominator);
+
+        assert_eq!(result, None);
+    }
+
+    /********** try_fixed_mul **********/
+
+    #[test]
+    fn test_try_fixed_mul_divide_by_zero() {
+        let result = u128::MAX.try_fixed_mul_floor(2, 0);
+
+        assert_eq!(result, Err(FixedPointError::DivideByZero));
+    }
+
+    #[test]
+    fn test_try_fixed_mul_overflow() {
+        let result = u128::MAX.try_fixed_mul_ceil(3, 2);
+
+        assert_eq!(result, Err(FixedPointError::Overflow));
+    }
+
+    #[test]
+    fn test_parse_fixed() {
+        const MAX: Option<u128> = parse_fixed("34028236692093846346.3374607431768211455", 19);
+
+        assert_eq!(MAX, Some(u128::MAX));
+        assert_eq!(parse_fixed("1_000.5", 7), Some(1000_5000000));
+        assert_eq!(parse_fixed("-0", 7), Some(0));
+        assert_eq!(parse_fixed("-1", 7), None);
+        assert_eq!(
+            parse_fixed("34028236692093846346.3374607431768211456", 19),
+            None
+        );
+        assert_eq!(parse_fixed("1.23456789", 7), None);
+    }
+}
@@ -0,0 +1,456 @@
+/// An unsigned 256-bit integer, used as a widened intermediate for 128-bit math.
+///
+/// Only supports the operations required by the fixed-point implementations.
+// field order matters for the derived ordering
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct U256 {
+    hi: u128,
+    lo: u128,
+}
+
+const LOW_64_MASK: u128 = u64::MAX as u128;
+
+impl U256 {
+    pub const fn from_u128(value: u128) -> U256 {
+        U256 { hi: 0, lo: value }
+    }
+
+    pub const fn is_zero(&self) -> bool {
+        self.hi == 0 && self.lo == 0
+    }
+
+    pub const fn leading_zeros(&self) -> u32 {
+        if self.hi == 0 {
+            128 + self.lo.leading_zeros()
+        } else {
+            self.hi.leading_zeros()
+        }
+    }
+
+    /// Returns the value as a u128, or None if it does not fit
+    pub const fn to_u128(self) -> Option<u128> {
+        if self.hi == 0 {
+            Some(self.lo)
+        } else {
+            None
+        }
+    }
+
+    /// Calculates the full 256-bit product of x and y
+    pub const fn mul_u128(x: u128, y: u128) -> U256 {
+        let (x_hi, x_lo) = (x >> 64, x & LOW_64_MASK);
+        let (y_hi, y_lo) = (y >> 64, y & LOW_64_MASK);
+
+        let lo_lo = x_lo * y_lo;
+        let hi_lo = x_hi * y_lo;
+        let lo_hi = x_lo * y_hi;
+        let hi_hi = x_hi * y_hi;
+
+        // sum of three values less than 2^64 cannot overflow a u128
+        let mid = (lo_lo >> 64) + (hi_lo & LOW_64_MASK) + (lo_hi & LOW_64_MASK);
+        U256 {
+            hi: hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (mid >> 64),
+            lo: (mid << 64) | (lo_lo & LOW_64_MASK),
+        }
+    }
+
+    /// Calculates self * value. Returns None if the result overflows.
+    pub const fn checked_mul_u128(self, value: u128) -> Option<U256> {
+        let high = U256::mul_u128(self.hi, value);
+        if high.hi != 0 {
+            return None;
+        }
+        let low = U256::mul_u128(self.lo, value);
+        match low.hi.checked_add(high.lo) {
+            Some(hi) => Some(U256 { hi, lo: low.lo }),
+            None => None,
+        }
+    }
+
+    /// Calculates self + value. Returns None if the result overflows.
+    pub const fn checked_add_u128(self, value: u128) -> Option<U256> {
+        let (lo, carry) = self.lo.overflowing_add(value);
+        if carry {
+            match self.hi.checked_add(1) {
+                Some(hi) => Some(U256 { hi, lo }),
+                None => None,
+            }
+        } else {
+            Some(U256 { hi: self.hi, lo })
+        }
+    }
+
+    /// Calculates self + rhs. Returns None if the result overflows.
+    pub const fn checked_add(self, rhs: U256) -> Option<U256> {
+        let (lo, carry) = self.lo.overflowing_add(rhs.lo);
+        let hi = match self.hi.checked_add(rhs.hi) {
+            Some(hi) => hi,
+            None => return None,
+        };
+        if carry {
+            match hi.checked_add(1) {
+                Some(hi) => Some(U256 { hi, lo }),
+                None => None,
+            }
+        } else {
+            Some(U256 { hi, lo })
+        }
+    }
+
+    /// Calculates self - rhs. Returns None if the result is negative.
+    pub const fn checked_sub(self, rhs: U256) -> Option<U256> {
+        let (lo, borrow) = self.lo.overflowing_sub(rhs.lo);
+        let hi = match self.hi.checked_sub(rhs.hi) {
+            Some(hi) => hi,
+            None => return None,
+        };
+        if borrow {
+            match hi.checked_sub(1) {
+                Some(hi) => Some(U256 { hi, lo }),
+                None => None,
+            }
+        } else {
+            Some(U256 { hi, lo })
+        }
+    }
+
+    /// Shifts the value left by `bits`. Returns None if any set bits are shifted out.
+    pub const fn checked_shl(self, bits: u32) -> Option<U256> {
+        if self.is_zero() || bits == 0 {
+            return Some(self);
+        }
+        if bits > self.leading_zeros() {
+            return None;
+        }
+        if bits >= 128 {
+            Some(U256 {
+                hi: self.lo << (bits - 128),
+                lo: 0,
+            })
+        } else {
+            Some(U256 {
+                hi: (self.hi << bits) | (self.lo >> (128 - bits)),
+                lo: self.lo << bits,
+            })
+        }
+    }
+
+    /// Shifts the value right by `bits`, discarding the shifted out bits
+    pub const fn shr(self, bits: u32) -> U256 {
+        if bits == 0 {
+            self
+        } else if bits >= 256 {
+            U256 { hi: 0, lo: 0 }
+        } else if bits >= 128 {
+            U256 {
+                hi: 0,
+                lo: self.hi >> (bits - 128),
+            }
+        } else {
+            U256 {
+                hi: self.hi >> bits,
+                lo: (self.lo >> bits) | (self.hi << (128 - bits)),
+            }
+        }
+    }
+
+    /// Calculates the quotient and remainder of self / divisor. Returns None if
+    /// the divisor is 0.
+    pub const fn div_rem_u128(self, divisor: u128) -> Option<(U256, u128)> {
+        if divisor == 0 {
+            return None;
+        }
+        if self.hi == 0 {
+            return Some((U256::from_u128(self.lo / divisor), self.lo % divisor));
+        }
+
+        // long division of the low half, carrying the remainder of the high half
+        let q_hi = self.hi / divisor;
+        let mut remainder = self.hi % divisor;
+        let mut q_lo: u128 = 0;
+        let mut i = 128;
+        while i > 0 {
+            i -= 1;
+            let carry = remainder >> 127;
+            remainder = (remainder << 1) | ((self.lo >> i) & 1);
+            q_lo <<= 1;
+            if carry == 1 || remainder >= divisor {
+                remainder = remainder.wrapping_sub(divisor);
+                q_lo |= 1;
+            }
+        }
+        Some((U256 { hi: q_hi, lo: q_lo }, remainder))
+    }
+
+    /// Calculates the quotient and remainder of self / divisor. Returns None if
+    /// the divisor is 0.
+    pub const fn div_rem(self, divisor: U256) -> Option<(U256, U256)> {
+        if divisor.hi == 0 {
+            return match self.div_rem_u128(divisor.lo) {
+                Some((quotient, remainder)) => Some((quotient, U256::from_u128(remainder))),
+                None => None,
+            };
+        }
+
+        // the divisor is at least 2^128, so the quotient fits in a u128. Subtract the
+        // divisor shifted to every bit of the quotient, from the highest one down
+        let mut remainder = self;
+        let mut quotient: u128 = 0;
+        if divisor.leading_zeros() >= self.leading_zeros() {
+            let shift = divisor.leading_zeros() - self.leading_zeros();
+            let mut i = shift + 1;
+            while i > 0 {
+                i -= 1;
+                let shifted = match divisor.checked_shl(i) {
+                    Some(shifted) => shifted,
+                    None => U256 { hi: 0, lo: 0 },
+                };
+                if let Some(rest) = remainder.checked_sub(shifted) {
+                    remainder = rest;
+                    quotient |= 1 << i;
+                }
+            }
+        }
+        Some((U256::from_u128(quotient), remainder))
+    }
+
+    /// Calculates floor(sqrt(self)) using Newton's method
+    pub const fn sqrt(self) -> u128 {
+        if self.is_zero() {
+            return 0;
+        }
+        // start from a power of two above the root, and iterate down towards it
+        let bits = 256 - self.leading_zeros();
+        let exp = bits.div_ceil(2);
+        let mut guess = if exp >= 128 { u128::MAX } else { 1 << exp };
+        loop {
+            // a quotient above the guess means the guess has converged
+            let quotient = match self.div_rem_u128(guess) {
+                Some((quotient, _)) if quotient.hi == 0 => quotient.lo,
+                _ => return guess,
+            };
+            // (guess + quotient) / 2 without overflowing
+            let next = (guess >> 1) + (quotient >> 1) + (guess & quotient & 1);
+            if next >= guess {
+                return guess;
+            }
+            guess = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mul_u128_max() {
+        let result = U256::mul_u128(u128::MAX, u128::MAX);
+
+        // (2^128 - 1)^2 = 2^256 - 2^129 + 1
+        assert_eq!(
+            result,
+            U256 {
+                hi: u128::MAX - 1,
+                lo: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_shifts() {
+        let value = U256::from_u128(0b1011 << 120);
+
+        let shifted = value.checked_shl(130).unwrap();
+
+        assert_eq!(
+            shifted,
+            U256 {
+                hi: 0b1011 << 122,
+                lo: 0
+            }
+        );
+        assert_eq!(shifted.shr(130), value);
+        assert_eq!(value.checked_shl(133), None);
+        assert_eq!(value.shr(300), U256::from_u128(0));
+    }
+
+    #[test]
+    fn test_div_rem_u128_recovers_factors() {
+        let x: u128 = 340_282_366_920_938_463_463_374_607_431;
+        let y: u128 = 1_000_000_000_000_000_007;
+
+        let (quotient, remainder) = U256::mul_u128(x, y).div_rem_u128(y).unwrap();
+
+        assert_eq!(quotient.to_u128(), Some(x));
+        assert_eq!(remainder, 0);
+    }
+
+    #[test]
+    fn test_div_rem_u128_remainder() {
+        let product = U256::mul_u128(u128::MAX, u128::MAX);
+
+        let (quotient, remainder) = product.div_rem_u128(u128::MAX - 1).unwrap();
+
+        // (2^128 - 1)^2 = (2^128 - 2) * 2^128 + 1
+        assert_eq!(quotient, U256 { hi: 1, lo: 0 });
+        assert_eq!(remainder, 1);
+    }
+
+    #[test]
+    fn test_div_rem_wide_divisor() {
+        let product = U256::mul_u128(u128::MAX, u128::MAX);
+
+        let (quotient, remainder) = product.div_rem(U256 { hi: 1, lo: 5 }).unwrap();
+
+        assert_eq!(quotient, U256::from_u128(u128::MAX - 6));
+        assert_eq!(remainder, U256::from_u128(36));
+        assert_eq!(
+            U256::from_u128(7).div_rem(product),
+            Some((U256::from_u128(0), U256::from_u128(7)))
+        );
+        assert_eq!(
+            product.div_rem(product),
+            Some((U256::from_u128(1), U256::from_u128(0)))
+        );
+        assert_eq!(product.div_rem(U256::from_u128(0)), None);
+    }
+
+    #[test]
+    fn test_checked_add_u128() {
+        let value = U256 {
+            hi: 1,
+            lo: u128::MAX,
+        };
+
+        assert_eq!(value.checked_add_u128(1), Some(U256 { hi: 2, lo: 0 }));
+        assert_eq!(
+            U256::mul_u128(u128::MAX, u128::MAX).checked_add_u128(u128::MAX),
+            Some(U256 {
+                hi: u128::MAX,
+                lo: 0
+            })
+        );
+        assert_eq!(
+            U256 {
+                hi: u128::MAX,
+                lo: 1
+            }
+            .checked_add_u128(u128::MAX),
+            None
+        );
+    }
+
+    #[test]
+    fn test_checked_add() {
+        let value = U256 {
+            hi: 1,
+            lo: u128::MAX,
+        };
+
+        assert_eq!(
+            value.checked_add(value),
+            Some(U256 {
+                hi: 3,
+                lo: u128::MAX - 1
+            })
+        );
+        assert_eq!(
+            U256 {
+                hi: u128::MAX,
+                lo: 0
+            }
+            .checked_add(U256 { hi: 0, lo: 1 }),
+            Some(U256 {
+                hi: u128::MAX,
+                lo: 1
+            })
+        );
+        assert_eq!(
+            U256 {
+                hi: u128::MAX,
+                lo: u128::MAX
+            }
+            .checked_add(U256::from_u128(1)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_checked_mul_u128() {
+        let value = U256 {
+            hi: 1,
+            lo: u128::MAX,
+        };
+
+        assert_eq!(
+            value.checked_mul_u128(2),
+            Some(U256 {
+                hi: 3,
+                lo: u128::MAX - 1
+            })
+        );
+        assert_eq!(value.checked_mul_u128(0), Some(U256::from_u128(0)));
+        assert_eq!(
+            U256::from_u128(u128::MAX).checked_mul_u128(u128::MAX),
+            Some(U256::mul_u128(u128::MAX, u128::MAX))
+        );
+        assert_eq!(value.checked_mul_u128(u128::MAX), None);
+        assert_eq!(
+            U256 {
+                hi: u128::MAX / 2 + 1,
+                lo: 0
+            }
+            .checked_mul_u128(2),
+            None
+        );
+    }
+
+    #[test]
+    fn test_checked_sub() {
+        let value = U256 { hi: 3, lo: 0 };
+
+        assert_eq!(
+            value.checked_sub(U256 { hi: 1, lo: 1 }),
+            Some(U256 {
+                hi: 1,
+                lo: u128::MAX
+            })
+        );
+        assert_eq!(value.checked_sub(value), Some(U256::from_u128(0)));
+        assert_eq!(U256::from_u128(1).checked_sub(U256::from_u128(2)), None);
+        assert_eq!(value.checked_sub(U256 { hi: 3, lo: 1 }), None);
+    }
+
+    #[test]
+    fn test_sqrt() {
+        assert_eq!(U256::from_u128(0).sqrt(), 0);
+        assert_eq!(U256::from_u128(1).sqrt(), 1);
+        assert_eq!(U256::from_u128(15).sqrt(), 3);
+        assert_eq!(U256::from_u128(16).sqrt(), 4);
+        assert_eq!(U256::mul_u128(u128::MAX, u128::MAX).sqrt(), u128::MAX);
+        assert_eq!(
+            U256::mul_u128(u128::MAX, u128::MAX)
+                .checked_add_u128(u128::MAX)
+                .unwrap()
+                .sqrt(),
+            u128::MAX
+        );
+        // floor(sqrt(2^255)) = 240615969168004511545033772477625056927
+        assert_eq!(
+            U256 {
+                hi: 1 << 127,
+                lo: 0
+            }
+            .sqrt(),
+            240_615_969_168_004_511_545_033_772_477_625_056_927
+        );
+    }
+
+    #[test]
+    fn test_div_rem_u128_divide_by_zero() {
+        let result = U256::from_u128(1).div_rem_u128(0);
+
+        assert_eq!(result, None);
+    }
+}
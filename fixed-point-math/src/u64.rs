@@ -1,59 +1,101 @@
-use crate::fixed_point::FixedPoint;
+//! The `FixedPoint` implementation for `u64`, with its routines also available as
+//! `const fn`s for protocol parameters that are computed at compile time
 
+#[allow(deprecated)]
+use crate::{
+    error::FixedPointError,
+    fixed_point::FixedPoint,
+    rounding::{Remainder, Rounding},
+};
+
+#[allow(deprecated)]
 impl FixedPoint for u64 {
-    fn fixed_mul_floor(self, y: u64, denominator: u64) -> Option<u64> {
-        mul_div_floor(self, y, denominator)
+    fn try_fixed_mul(
+        self,
+        y: u64,
+        denominator: u64,
+        rounding: Rounding,
+    ) -> Result<u64, FixedPointError> {
+        try_fixed_mul(self, y, denominator, rounding)
     }
+}
 
-    fn fixed_mul_ceil(self, y: u64, denominator: u64) -> Option<u64> {
-        mul_div_ceil(self, y, denominator)
+/// Safely calculates x * y / denominator, rounded in the direction of `rounding`.
+/// Returns an error if the result does not fit in a u64 or if the denominator is 0.
+///
+/// The product is computed as a u128, such that only the final result can overflow.
+pub const fn try_fixed_mul(
+    x: u64,
+    y: u64,
+    denominator: u64,
+    rounding: Rounding,
+) -> Result<u64, FixedPointError> {
+    if denominator == 0 {
+        return Err(FixedPointError::DivideByZero);
     }
-
-    fn fixed_div_floor(self, y: u64, denominator: u64) -> Option<u64> {
-        mul_div_floor(self, denominator, y)
+    let r = (x as u128) * (y as u128);
+    let z = denominator as u128;
+    let quotient = r / z;
+    if quotient > u64::MAX as u128 {
+        return Err(FixedPointError::Overflow);
     }
-
-    fn fixed_div_ceil(self, y: u64, denominator: u64) -> Option<u64> {
-        mul_div_ceil(self, denominator, y)
+    let quotient = quotient as u64;
+    let remainder = Remainder::from_u128(r % z, z);
+    if rounding.round_away_from_zero(false, quotient % 2 == 1, remainder) {
+        match quotient.checked_add(1) {
+            Some(quotient) => Ok(quotient),
+            None => Err(FixedPointError::Overflow),
+        }
+    } else {
+        Ok(quotient)
     }
 }
 
-/// Performs floor(x * y / z)
-fn mul_div_floor(x: u64, y: u64, z: u64) -> Option<u64> {
-    return match x.checked_mul(y) {
-        Some(r) => r.checked_div(z),
-        None => {
-            let res_u128 = (x as u128).checked_mul(y as u128)?.checked_div(z as u128)?;
-            if res_u128 > u64::MAX as u128 {
-                return None;
-            }
-            Some(res_u128 as u64)
-        }
-    };
+/// Safely calculates x * denominator / y, rounded in the direction of `rounding`.
+/// Returns an error if the result does not fit in a u64 or if y is 0.
+pub const fn try_fixed_div(
+    x: u64,
+    y: u64,
+    denominator: u64,
+    rounding: Rounding,
+) -> Result<u64, FixedPointError> {
+    try_fixed_mul(x, denominator, y, rounding)
 }
 
-/// Performs ceil(x * y / z)
-fn mul_div_ceil(x: u64, y: u64, z: u64) -> Option<u64> {
-    return match x.checked_mul(y) {
-        Some(r) => {
-            let remainder = r.checked_rem_euclid(z)?;
-            // div overflow will be caught by checked_rem_euclid
-            (r / z).checked_add(if remainder > 0 { 1 } else { 0 })
-        }
-        None => {
-            // TODO: safe cast
-            let r_u128 = (x as u128).checked_mul(y as u128)?;
-            let remainder = r_u128.checked_rem_euclid(z as u128)?;
-            let res_u128 = r_u128 / (z as u128);
-            if res_u128 > u64::MAX as u128 {
-                return None;
-            }
-            (res_u128 as u64).checked_add(if remainder > 0 { 1 } else { 0 })
-        }
-    };
+/// Safely calculates floor(x * y / denominator). Returns None if the result overflows or
+/// if the denominator is 0.
+pub const fn fixed_mul_floor(x: u64, y: u64, denominator: u64) -> Option<u64> {
+    ok(try_fixed_mul(x, y, denominator, Rounding::Floor))
+}
+
+/// Safely calculates ceil(x * y / denominator). Returns None if the result overflows or
+/// if the denominator is 0.
+pub const fn fixed_mul_ceil(x: u64, y: u64, denominator: u64) -> Option<u64> {
+    ok(try_fixed_mul(x, y, denominator, Rounding::Ceil))
+}
+
+/// Safely calculates floor(x * denominator / y). Returns None if the result overflows or
+/// if y is 0.
+pub const fn fixed_div_floor(x: u64, y: u64, denominator: u64) -> Option<u64> {
+    ok(try_fixed_div(x, y, denominator, Rounding::Floor))
+}
+
+/// Safely calculates ceil(x * denominator / y). Returns None if the result overflows or
+/// if y is 0.
+pub const fn fixed_div_ceil(x: u64, y: u64, denominator: u64) -> Option<u64> {
+    ok(try_fixed_div(x, y, denominator, Rounding::Ceil))
+}
+
+/// Converts a result into an option, usable in `const` contexts
+const fn ok(result: Result<u64, FixedPointError>) -> Option<u64> {
+    match result {
+        Ok(value) => Some(value),
+        Err(_) => None,
+    }
 }
 
 #[cfg(test)]
+#[allow(deprecated)]
 mod tests {
     use super::*;
 
@@ -240,4 +282,156 @@ mod tests {
 
         assert_eq!(result, None);
     }
+
+    /********** fixed_mul **********/
+
+    #[test]
+    fn test_fixed_mul_toward_and_away_from_zero() {
+        let x: u64 = 1_5391283;
+        let y: u64 = 314_1592653;
+        let denominator: u64 = 1_0000001;
+
+        let toward = x.fixed_mul(y, denominator, Rounding::TowardZero).unwrap();
+        let away = x.fixed_mul(y, denominator, Rounding::AwayFromZero).unwrap();
+
+        assert_eq!(toward, 483_5313675);
+        assert_eq!(away, 483_5313676);
+    }
+
+    #[test]
+    fn test_fixed_mul_half_up_rounds_to_nearest() {
+        let x: u64 = 5;
+        let y: u64 = 9_999_999;
+        let denominator: u64 = 2_0000000;
+
+        let result = x.fixed_mul(y, denominator, Rounding::HalfUp).unwrap();
+
+        assert_eq!(result, 2)
+    }
+
+    #[test]
+    fn test_fixed_mul_half_up_ties_away_from_zero() {
+        let x: u64 = 5;
+        let y: u64 = 1_0000000;
+        let denominator: u64 = 2_0000000;
+
+        let result = x.fixed_mul(y, denominator, Rounding::HalfUp).unwrap();
+
+        assert_eq!(result, 3)
+    }
+
+    #[test]
+    fn test_fixed_mul_half_even_ties_to_even() {
+        let x: u64 = 5;
+        let y: u64 = 1_0000000;
+        let denominator: u64 = 2_0000000;
+
+        let result = x.fixed_mul(y, denominator, Rounding::HalfEven).unwrap();
+
+        assert_eq!(result, 2)
+    }
+
+    #[test]
+    fn test_fixed_mul_half_even_odd_ties_to_even() {
+        let x: u64 = 7;
+        let y: u64 = 1_0000000;
+        let denominator: u64 = 2_0000000;
+
+        let result = x.fixed_mul(y, denominator, Rounding::HalfEven).unwrap();
+
+        assert_eq!(result, 4)
+    }
+
+    #[test]
+    fn test_fixed_mul_half_even_rounds_to_nearest() {
+        let x: u64 = 5;
+        let y: u64 = 1_0000001;
+        let denominator: u64 = 2_0000000;
+
+        let result = x.fixed_mul(y, denominator, Rounding::HalfEven).unwrap();
+
+        assert_eq!(result, 3)
+    }
+
+    #[test]
+    fn test_fixed_mul_half_even_phantom_overflow_uses_u128() {
+        let x: u64 = 18_446_744_077;
+        let y: u64 = 1_000_000_000;
+        let denominator: u64 = 2_000_000_000;
+
+        let half_even = x.fixed_mul(y, denominator, Rounding::HalfEven).unwrap();
+        let half_up = x.fixed_mul(y, denominator, Rounding::HalfUp).unwrap();
+
+        assert_eq!(half_even, 9_223_372_038);
+        assert_eq!(half_up, 9_223_372_039);
+    }
+
+    /********** fixed_div **********/
+
+    #[test]
+    fn test_fixed_div_half_up() {
+        let x: u64 = 314_1592653;
+        let y: u64 = 1_5391280;
+        let denominator: u64 = 1_0000000;
+
+        let result = x.fixed_div(y, denominator, Rounding::HalfUp).unwrap();
+
+        assert_eq!(result, 204_1150998)
+    }
+
+    #[test]
+    fn test_fixed_div_half_even() {
+        let x: u64 = 5;
+        let y: u64 = 2_0000000;
+        let denominator: u64 = 1_0000000;
+
+        let result = x.fixed_div(y, denominator, Rounding::HalfEven).unwrap();
+
+        assert_eq!(result, 2)
+    }
+
+    /********** try_fixed_mul **********/
+
+    #[test]
+    fn test_try_fixed_mul_divide_by_zero() {
+        let result = 1_0000000_u64.try_fixed_mul_floor(1_0000000, 0);
+
+        assert_eq!(result, Err(FixedPointError::DivideByZero));
+    }
+
+    #[test]
+    fn test_try_fixed_mul_overflow() {
+        let result =
+            18_446_744_073_000_000_000_u64.try_fixed_mul_ceil(2_000_000_000, 1_000_000_000);
+
+        assert_eq!(result, Err(FixedPointError::Overflow));
+    }
+
+    #[test]
+    fn test_try_fixed_div_divide_by_zero() {
+        let result = 1_0000000_u64.try_fixed_div(0, 1_0000000, Rounding::HalfUp);
+
+        assert_eq!(result, Err(FixedPointError::DivideByZero));
+    }
+
+    /********** const **********/
+
+    #[test]
+    fn test_const_fixed_mul_div() {
+        const FEE: Option<u64> = fixed_mul_ceil(1000_0000001, 30, 10_000);
+        const SHARES: Option<u64> = fixed_div_floor(10_0000000, 3_0000000, 1_0000000);
+
+        assert_eq!(FEE, Some(3_0000001));
+        assert_eq!(fixed_mul_floor(1000_0000001, 30, 10_000), Some(3_0000000));
+        assert_eq!(SHARES, Some(3_3333333));
+        assert_eq!(
+            fixed_div_ceil(10_0000000, 3_0000000, 1_0000000),
+            Some(3_3333334)
+        );
+        assert_eq!(fixed_mul_floor(u64::MAX, 2, 1), None);
+        assert_eq!(
+            try_fixed_mul(1, 1, 0, Rounding::Floor),
+            Err(FixedPointError::DivideByZero)
+        );
+    }
 }
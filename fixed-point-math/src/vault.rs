@@ -0,0 +1,268 @@
+//! Share conversion math for ERC-4626 style vaults
+//!
+//! Each preview rounds in the direction that favors the vault, such that shares can
+//! never be redeemed for more assets than were deposited for them.
+
+#[allow(deprecated)]
+use crate::{
+    error::FixedPointError, fixed_point::FixedPoint, integer::Integer, rounding::Rounding,
+};
+
+/// The state of a vault, used to convert between assets and shares
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Vault<T> {
+    /// The total assets held by the vault
+    pub total_assets: T,
+    /// The total shares issued by the vault
+    pub total_shares: T,
+    /// The virtual share offset, as a power of 10, if enabled
+    virtual_offset: Option<u32>,
+}
+
+#[allow(deprecated)]
+impl<T: FixedPoint + Integer> Vault<T> {
+    /// Creates a vault without virtual shares. The first deposit is converted 1:1,
+    /// which leaves the first depositor exposed to inflation attacks.
+    pub fn new(total_assets: T, total_shares: T) -> Self {
+        Vault {
+            total_assets,
+            total_shares,
+            virtual_offset: None,
+        }
+    }
+
+    /// Enables virtual shares and assets, such that the vault converts as if it held
+    /// `10^offset` more shares and 1 more asset.
+    ///
+    /// This makes inflating the share price by donating assets to the vault
+    /// unprofitable, and an offset of 3 to 6 is enough for most assets.
+    pub fn with_virtual_offset(self, offset: u32) -> Self {
+        Vault {
+            virtual_offset: Some(offset),
+            ..self
+        }
+    }
+
+    /// Safely converts assets to shares, rounded in the direction of `rounding`.
+    /// Returns an error if any value is negative, if the result does not fit in `T`,
+    /// or if the vault has shares but no assets.
+    pub fn try_convert_to_shares(
+        &self,
+        assets: T,
+        rounding: Rounding,
+    ) -> Result<T, FixedPointError> {
+        self.check(assets)?;
+        match self.virtual_offset {
+            Some(offset) => {
+                let (virtual_assets, virtual_shares) = self.virtual_totals(offset)?;
+                assets.try_fixed_mul(virtual_shares, virtual_assets, rounding)
+            }
+            None if self.total_shares == T::ZERO => Ok(assets),
+            None => assets.try_fixed_mul(self.total_shares, self.total_assets, rounding),
+        }
+    }
+
+    /// Safely converts shares to assets, rounded in the direction of `rounding`.
+    /// Returns an error if any value is negative or if the result does not fit in `T`.
+    pub fn try_convert_to_assets(
+        &self,
+        shares: T,
+        rounding: Rounding,
+    ) -> Result<T, FixedPointError> {
+        self.check(shares)?;
+        match self.virtual_offset {
+            Some(offset) => {
+                let (virtual_assets, virtual_shares) = self.virtual_totals(offset)?;
+                shares.try_fixed_mul(virtual_assets, virtual_shares, rounding)
+            }
+            None if self.total_shares == T::ZERO => Ok(shares),
+            None => shares.try_fixed_mul(self.total_assets, self.total_shares, rounding),
+        }
+    }
+
+    /// Safely converts assets to shares, rounded in the direction of `rounding`.
+    /// Returns None if any value is negative, if the result overflows, or if the
+    /// vault has shares but no assets.
+    pub fn convert_to_shares(&self, assets: T, rounding: Rounding) -> Option<T> {
+        self.try_convert_to_shares(assets, rounding).ok()
+    }
+
+    /// Safely converts shares to assets, rounded in the direction of `rounding`.
+    /// Returns None if any value is negative or if the result overflows.
+    pub fn convert_to_assets(&self, shares: T, rounding: Rounding) -> Option<T> {
+        self.try_convert_to_assets(shares, rounding).ok()
+    }
+
+    /// Safely calculates the shares minted for depositing `assets`, rounded down.
+    /// Returns an error if the conversion fails.
+    pub fn try_preview_deposit(&self, assets: T) -> Result<T, FixedPointError> {
+        self.try_convert_to_shares(assets, Rounding::Floor)
+    }
+
+    /// Safely calculates the assets required to mint `shares`, rounded up. Returns an
+    /// error if the conversion fails.
+    pub fn try_preview_mint(&self, shares: T) -> Result<T, FixedPointError> {
+        self.try_convert_to_assets(shares, Rounding::Ceil)
+    }
+
+    /// Safely calculates the shares burned to withdraw `assets`, rounded up. Returns
+    /// an error if the conversion fails.
+    pub fn try_preview_withdraw(&self, assets: T) -> Result<T, FixedPointError> {
+        self.try_convert_to_shares(assets, Rounding::Ceil)
+    }
+
+    /// Safely calculates the assets returned for redeeming `shares`, rounded down.
+    /// Returns an error if the conversion fails.
+    pub fn try_preview_redeem(&self, shares: T) -> Result<T, FixedPointError> {
+        self.try_convert_to_assets(shares, Rounding::Floor)
+    }
+
+    /// Safely calculates the shares minted for depositing `assets`, rounded down.
+    /// Returns None if the conversion fails.
+    pub fn preview_deposit(&self, assets: T) -> Option<T> {
+        self.try_preview_deposit(assets).ok()
+    }
+
+    /// Safely calculates the assets required to mint `shares`, rounded up. Returns
+    /// None if the conversion fails.
+    pub fn preview_mint(&self, shares: T) -> Option<T> {
+        self.try_preview_mint(shares).ok()
+    }
+
+    /// Safely calculates the shares burned to withdraw `assets`, rounded up. Returns
+    /// None if the conversion fails.
+    pub fn preview_withdraw(&self, assets: T) -> Option<T> {
+        self.try_preview_withdraw(assets).ok()
+    }
+
+    /// Safely calculates the assets returned for redeeming `shares`, rounded down.
+    /// Returns None if the conversion fails.
+    pub fn preview_redeem(&self, shares: T) -> Option<T> {
+        self.try_preview_redeem(shares).ok()
+    }
+
+    fn check(&self, amount: T) -> Result<(), FixedPointError> {
+        if amount < T::ZERO || self.total_assets < T::ZERO || self.total_shares < T::ZERO {
+            return Err(FixedPointError::InvalidInput);
+        }
+        Ok(())
+    }
+
+    /// Returns the total assets and shares including the virtual amounts
+    fn virtual_totals(&self, offset: u32) -> Result<(T, T), FixedPointError> {
+        let virtual_shares = T::TEN
+            .checked_pow(offset)
+            .and_then(|virtual_shares| self.total_shares.checked_add(virtual_shares))
+            .ok_or(FixedPointError::Overflow)?;
+        let virtual_assets = self
+            .total_assets
+            .checked_add(T::ONE)
+            .ok_or(FixedPointError::Overflow)?;
+        Ok((virtual_assets, virtual_shares))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_vault_converts_one_to_one() {
+        let vault = Vault::new(0_i128, 0);
+
+        assert_eq!(vault.preview_deposit(1_0000000), Some(1_0000000));
+        assert_eq!(vault.preview_mint(1_0000000), Some(1_0000000));
+        assert_eq!(vault.preview_withdraw(1_0000000), Some(1_0000000));
+        assert_eq!(vault.preview_redeem(1_0000000), Some(1_0000000));
+    }
+
+    #[test]
+    fn test_previews_round_in_favor_of_vault() {
+        let vault = Vault::new(1000_u64, 500);
+
+        // 1 share = 2 assets
+        assert_eq!(vault.preview_deposit(123), Some(61));
+        assert_eq!(vault.preview_withdraw(123), Some(62));
+        assert_eq!(vault.preview_mint(123), Some(246));
+        assert_eq!(vault.preview_redeem(123), Some(246));
+
+        let vault = Vault::new(500_u64, 1000);
+
+        // 1 share = 0.5 assets
+        assert_eq!(vault.preview_mint(123), Some(62));
+        assert_eq!(vault.preview_redeem(123), Some(61));
+    }
+
+    #[test]
+    fn test_round_trip_never_gains() {
+        let vault = Vault::new(1000000_0000007_i128, 999999_0000003);
+
+        for assets in [1, 3, 1_0000000, 12_3456789, 999999_9999999] {
+            let shares = vault.preview_deposit(assets).unwrap();
+            assert!(vault.preview_redeem(shares).unwrap() <= assets);
+
+            let shares = vault.preview_withdraw(assets).unwrap();
+            assert!(vault.preview_redeem(shares).unwrap() >= assets);
+        }
+    }
+
+    #[test]
+    fn test_virtual_offset_limits_inflation_attack() {
+        let donation: i128 = 10000_0000000;
+
+        // the attacker deposits 1 asset, and then donates assets to the vault
+        let vault = Vault::new(1 + donation, 1);
+        assert_eq!(vault.preview_deposit(donation), Some(0));
+
+        let virtual_vault = Vault::new(0_i128, 0).with_virtual_offset(3);
+        let attacker_shares = virtual_vault.preview_deposit(1).unwrap();
+        assert_eq!(attacker_shares, 1000);
+        let virtual_vault = Vault::new(1 + donation, attacker_shares).with_virtual_offset(3);
+        assert_eq!(virtual_vault.preview_deposit(donation), Some(1999));
+    }
+
+    #[test]
+    fn test_virtual_offset_conversions() {
+        let vault = Vault::new(1000_u64, 500_000).with_virtual_offset(3);
+
+        // (500,000 + 1,000) / (1,000 + 1) shares per asset
+        assert_eq!(vault.preview_deposit(7), Some(3503));
+        assert_eq!(vault.preview_withdraw(7), Some(3504));
+        assert_eq!(vault.preview_mint(3500), Some(7));
+        assert_eq!(vault.preview_redeem(3500), Some(6));
+    }
+
+    #[test]
+    fn test_phantom_overflow() {
+        let vault = Vault::new(i128::MAX / 2, i128::MAX / 4);
+
+        assert_eq!(vault.preview_deposit(i128::MAX / 2), Some(i128::MAX / 4));
+        assert_eq!(vault.preview_mint(i128::MAX / 4), Some(i128::MAX / 2));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Vault::new(0_i128, 10).try_preview_deposit(1),
+            Err(FixedPointError::DivideByZero)
+        );
+        assert_eq!(
+            Vault::new(10_i128, 10).try_preview_deposit(-1),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            Vault::new(-10_i128, 10).try_preview_redeem(1),
+            Err(FixedPointError::InvalidInput)
+        );
+        assert_eq!(
+            Vault::new(1_u64, u64::MAX).try_preview_deposit(2),
+            Err(FixedPointError::Overflow)
+        );
+        assert_eq!(
+            Vault::new(1_u32, 1)
+                .with_virtual_offset(10)
+                .try_preview_deposit(1),
+            Err(FixedPointError::Overflow)
+        );
+    }
+}